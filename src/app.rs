@@ -1,58 +1,355 @@
 use eframe::egui;
-use crate::core::disk::{DiskInfo, get_disks};
-use crate::core::scanner::{Scanner, FileNode, ScanMessage, ScanProgress};
-use crate::ui::{disk_select, tree};
-use std::path::PathBuf;
+use crate::core::alerts::notify_low_space;
+use crate::core::disk::{DiskInfo, MountWatcher, get_disks, storage_devices, disk_for_path, DISK_REFRESH_INTERVAL};
+use crate::core::duplicates::replace_with_hard_link;
+use crate::core::history::UsageHistory;
+use crate::core::{ncdu, report};
+use crate::core::remote::{RemoteAgent, RemoteMessage, Transport};
+use crate::core::scanner::{device_of, MultiScanner, FileNode, ScanMessage, ScanOptions, ScanProgress};
+use crate::core::settings::{Measure, Settings};
+use crate::core::transfer::{check_free_space, Transfer, TransferKind, TransferMessage, TransferProgress};
+use crate::ui::{age, alerts, charts, disk_select, duplicates, history, location, reclaim, remote, top_files, transfer, tree};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use humansize::{format_size, DECIMAL};
 
+pub const DEFAULT_WINDOW_SIZE: [f32; 2] = [1280.0, 720.0];
+
+const SEARCH_HELP: &str = "Words match names. Filters:\n\
+    ext:mp4,mkv   size:>1G   modified:<2024-01-01   accessed:>=2023-06-01\n\
+    path:*/cache/*   name:*.log   re:^core\\.\\d+$   /regex/   type:dir|file|video\n\
+    Prefix a term with - to exclude it.";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ViewTab {
+    Tree,
+    TopFiles,
+    Types,
+    Age,
+    Duplicates,
+    Reclaim,
+}
+
+impl ViewTab {
+    const ALL: [ViewTab; 6] = [ViewTab::Tree, ViewTab::TopFiles, ViewTab::Types, ViewTab::Age, ViewTab::Duplicates, ViewTab::Reclaim];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ViewTab::Tree => "🌲 Tree",
+            ViewTab::TopFiles => "📄 Top files",
+            ViewTab::Types => "📊 Types",
+            ViewTab::Age => "🕓 Age",
+            ViewTab::Duplicates => "👥 Duplicates",
+            ViewTab::Reclaim => "♻ Reclaimable",
+        }
+    }
+}
+
 pub struct GateApp {
     disks: Vec<DiskInfo>,
-    selected_disk_mount: Option<String>,
-    scanner: Option<Scanner>,
+    disks_read_at: Instant,
+    mount_watcher: MountWatcher,
+    usage_history: UsageHistory,
+    /// Why the usage history file could not be loaded; it is left alone
+    /// rather than saved over while this is set.
+    history_error: Option<String>,
+    history_mount: Option<String>,
+    /// Mounts already notified about, so each crossing alerts once.
+    low_space_notified: HashSet<String>,
+    alert_editor: alerts::AlertSettingsEditor,
+    scan_roots: Vec<PathBuf>,
+    /// Set when the tree was loaded from an ncdu export instead of scanned.
+    imported_from: Option<PathBuf>,
+    /// Connected remote host; while set, disks and scans are the host's.
+    remote: Option<remote::RemoteSession>,
+    remote_form: remote::ConnectForm,
+    show_remote_modal: bool,
+    disk_picker: disk_select::DiskPicker,
+    scanner: Option<MultiScanner>,
     root_node: Option<FileNode>,
     is_scanning: bool,
     scan_progress: ScanProgress,
     tree_view: tree::TreeView,
+    top_files: top_files::TopFilesView,
+    type_breakdown: charts::TypeBreakdownView,
+    age_view: age::AgeView,
+    duplicates: duplicates::DuplicatesView,
+    reclaim: reclaim::ReclaimView,
+    active_tab: ViewTab,
     error_message: Option<String>,
+    /// Why the settings file could not be loaded. While set, settings are
+    /// not saved, so the user's file is not replaced by defaults.
+    settings_error: Option<String>,
     zoom_factor: f32,
     show_disk_modal: bool,
     show_settings_modal: bool,
-    ui_scale: f32,
-    dark_mode: bool,
+    settings: Settings,
+    new_exclusion: String,
+    location_input: location::LocationInput,
+    move_source: Option<PathBuf>,
+    move_kind: TransferKind,
+    transfer: Option<Transfer>,
+    transfer_progress: Option<TransferProgress>,
 }
 
 impl GateApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        cc.egui_ctx.set_visuals(egui::Visuals::dark());
+    /// Starts on the home screen, or scanning `locations` if any were given
+    /// on the command line.
+    pub fn new(cc: &eframe::CreationContext<'_>, locations: Vec<PathBuf>) -> Self {
+        let (settings, settings_error) = match Settings::try_load() {
+            Ok(settings) => (settings, None),
+            Err(e) => (Settings::default(), Some(format!("{:#}", e))),
+        };
+        cc.egui_ctx.set_visuals(visuals(settings.appearance.dark_mode));
+        let (usage_history, history_error) = match UsageHistory::try_load() {
+            Ok(history) => (history, None),
+            Err(e) => (UsageHistory::default(), Some(format!("{:#}", e))),
+        };
 
-        Self {
-            disks: get_disks(),
-            selected_disk_mount: None,
+        let mut app = Self {
+            disks: Vec::new(),
+            disks_read_at: Instant::now(),
+            mount_watcher: MountWatcher::new(),
+            usage_history,
+            history_mount: None,
+            low_space_notified: HashSet::new(),
+            alert_editor: alerts::AlertSettingsEditor::new(),
+            scan_roots: Vec::new(),
+            imported_from: None,
+            remote: None,
+            remote_form: remote::ConnectForm::new(),
+            show_remote_modal: false,
+            disk_picker: disk_select::DiskPicker::new(),
             scanner: None,
             root_node: None,
             is_scanning: false,
             scan_progress: ScanProgress::default(),
-            tree_view: tree::TreeView::new(),
-            error_message: None,
-            zoom_factor: 1.0,
+            tree_view: tree::TreeView::new(&settings.tree),
+            top_files: top_files::TopFilesView::new(&settings.top_files),
+            type_breakdown: charts::TypeBreakdownView::new(),
+            age_view: age::AgeView::new(),
+            duplicates: duplicates::DuplicatesView::new(),
+            reclaim: reclaim::ReclaimView::new(),
+            active_tab: ViewTab::Tree,
+            error_message: settings_error.as_ref().map(|e| {
+                format!("{}\n\nSettings will not be saved until the file is fixed or reset in Settings.", e)
+            }).or_else(|| history_error.as_ref().map(|e| {
+                format!("{}\n\nUsage history will not be saved until the file is fixed or removed.", e)
+            })),
+            history_error,
+            settings_error,
+            zoom_factor: settings.appearance.zoom,
             show_disk_modal: false,
             show_settings_modal: false,
-            ui_scale: 1.35,
-            dark_mode: true,
+            settings,
+            new_exclusion: String::new(),
+            location_input: location::LocationInput::new(),
+            move_source: None,
+            move_kind: TransferKind::Move,
+            transfer: None,
+            transfer_progress: None,
+        };
+        app.refresh_disks();
+        if !locations.is_empty() {
+            app.open_locations(locations);
+        }
+        app
+    }
+
+    /// Opens disk mounts and/or folders for analysis and remembers them.
+    /// This ends a remote session, since the locations are on this machine.
+    fn open_locations(&mut self, paths: Vec<PathBuf>) {
+        if let Some(bad) = paths.iter().find(|p| !p.is_dir()) {
+            self.error_message = Some(format!("Not a folder: {}", bad.display()));
+            return;
+        }
+        let one_file_system = self.settings.scan.one_file_system;
+        let roots = distinct_roots(paths, |outer, inner| one_file_system && device_of(outer) != device_of(inner));
+        if roots.is_empty() {
+            return;
+        }
+        self.remote = None;
+        for root in roots.iter().rev() {
+            self.settings.push_recent(root.to_string_lossy().to_string());
+        }
+        self.settings.last_roots = roots.iter().map(|r| r.to_string_lossy().to_string()).collect();
+        self.save_settings();
+        self.scan_roots = roots;
+        self.imported_from = None;
+        self.start_scan();
+    }
+
+    /// Scans mounts of the connected remote host.
+    fn open_remote_locations(&mut self, paths: Vec<PathBuf>) {
+        // Remote locations are mount points, each its own filesystem.
+        let one_file_system = self.settings.scan.one_file_system;
+        let roots = distinct_roots(paths, |_, _| one_file_system);
+        if roots.is_empty() || self.remote.is_none() {
+            return;
+        }
+        self.scan_roots = roots;
+        self.imported_from = None;
+        self.start_scan();
+    }
+
+    fn connect_remote(&mut self, transport: Transport) {
+        if let Transport::Ssh { host, .. } = &transport {
+            self.settings.push_recent_host(host.clone());
+            self.save_settings();
+        }
+        match RemoteAgent::connect(transport) {
+            Ok(agent) => {
+                self.remote = Some(remote::RemoteSession::new(agent));
+                self.show_remote_modal = false;
+                self.show_disk_modal = true;
+            }
+            Err(e) => self.error_message = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Handles answers from the remote agent. A lost connection ends the
+    /// session and leaves its scan, as its paths no longer lead anywhere.
+    fn poll_remote(&mut self, ctx: &egui::Context) {
+        let Some(session) = &mut self.remote else {
+            return;
+        };
+        let mut disconnected = None;
+        while let Some(msg) = session.agent.try_recv() {
+            match msg {
+                RemoteMessage::Disks(disks) => session.set_disks(disks),
+                RemoteMessage::Scan(ScanMessage::Progress(p)) => self.scan_progress = p,
+                RemoteMessage::Scan(ScanMessage::Completed(node)) => {
+                    self.root_node = Some(node);
+                    self.is_scanning = false;
+                }
+                RemoteMessage::Scan(ScanMessage::Error(e)) | RemoteMessage::Error(e) => {
+                    self.error_message = Some(e);
+                    self.is_scanning = false;
+                }
+                RemoteMessage::Deleted(paths) => {
+                    if let Some(root) = &mut self.root_node {
+                        for path in &paths {
+                            root.remove_descendant(path);
+                        }
+                    }
+                }
+                RemoteMessage::Disconnected(reason) => disconnected = Some(format!("Disconnected from {}: {}", session.host(), reason)),
+            }
+        }
+        if self.show_disk_modal && session.disks_requested_at.elapsed() >= DISK_REFRESH_INTERVAL {
+            session.refresh_disks();
+        }
+        if let Some(message) = disconnected {
+            let was_remote_scan = self.imported_from.is_none() && !self.scan_roots.is_empty();
+            self.remote = None;
+            if was_remote_scan {
+                self.go_home();
+            }
+            self.error_message = Some(message);
+        } else {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+
+    /// Disks of the machine being looked at, remote or local.
+    fn scan_disks(&self) -> &[DiskInfo] {
+        match &self.remote {
+            Some(session) => &session.disks,
+            None => &self.disks,
+        }
+    }
+
+    /// Loads an `ncdu -o` export, typically made on another machine.
+    fn import_ncdu(&mut self, file: PathBuf) {
+        match ncdu::import(&file) {
+            Ok(root) => {
+                self.scan_roots = vec![root.path.clone()];
+                self.scan_progress = ScanProgress::default();
+                root.visit_files(&mut |_| self.scan_progress.files_scanned += 1);
+                self.root_node = Some(root);
+                self.imported_from = Some(file);
+                self.is_scanning = false;
+                self.scanner = None;
+                self.error_message = None;
+                self.duplicates.reset();
+            }
+            Err(e) => self.error_message = Some(format!("Import failed: {:#}", e)),
+        }
+    }
+
+    fn pick_ncdu_export() -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .set_title("Import ncdu export")
+            .add_filter("ncdu JSON", &["json"])
+            .pick_file()
+    }
+
+    /// Imported and remote trees describe another machine, so their paths
+    /// must not be touched here. Returns true after telling the user.
+    fn refuse_unless_local(&mut self, action: &str) -> bool {
+        let reason = if self.imported_from.is_some() {
+            "this scan was imported from an ncdu export".to_string()
+        } else if let Some(session) = &self.remote {
+            format!("this scan is of {}", session.host())
+        } else {
+            return false;
+        };
+        self.error_message = Some(format!("{} is not available: {}", action, reason));
+        true
+    }
+
+    fn pick_folders() -> Option<Vec<PathBuf>> {
+        rfd::FileDialog::new().set_title("Select folders to scan").pick_folders()
+    }
+
+    fn handle_dropped_folders(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+        let (dropped, files): (Vec<PathBuf>, Vec<PathBuf>) = dropped.into_iter().partition(|p| p.is_dir());
+        if let Some(export) = files.into_iter().find(|p| p.extension().is_some_and(|e| e == "json")) {
+            self.import_ncdu(export);
+        } else if !dropped.is_empty() {
+            self.open_locations(dropped);
+        }
+
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_overlay")));
+            let rect = ctx.screen_rect();
+            painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(180));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop folders to scan them",
+                egui::FontId::proportional(28.0),
+                egui::Color32::WHITE,
+            );
         }
     }
 
     fn start_scan(&mut self) {
-        if let Some(mount) = &self.selected_disk_mount {
+        if let Some(file) = self.imported_from.clone() {
+            self.import_ncdu(file);
+        } else if !self.scan_roots.is_empty() {
             self.is_scanning = true;
             self.root_node = None;
             self.error_message = None;
             self.scan_progress = ScanProgress::default();
-            self.scanner = Some(Scanner::new(PathBuf::from(mount)));
+            self.duplicates.reset();
+            match &mut self.remote {
+                Some(session) => session.agent.scan(self.scan_roots.clone(), self.settings.scan.clone()),
+                None => self.scanner = Some(MultiScanner::new(self.scan_roots.clone(), self.settings.scan.clone())),
+            }
         }
     }
     
     fn delete_item(&mut self, path: PathBuf) {
+        if let (Some(session), None) = (&mut self.remote, &self.imported_from) {
+            session.agent.delete(vec![path]);
+            return;
+        }
+        if self.refuse_unless_local("Deleting") {
+            return;
+        }
         match trash::delete(&path) {
             Ok(_) => {
                 self.start_scan();
@@ -63,24 +360,218 @@ impl GateApp {
         }
     }
 
+    fn start_transfer(&mut self, source: PathBuf, mount_point: String, kind: TransferKind) {
+        let Some(size) = self.root_node.as_ref().and_then(|r| r.find(&source)).map(|n| n.size) else {
+            return;
+        };
+        let Some(disk) = self.disks.iter().find(|d| d.mount_point == mount_point) else {
+            return;
+        };
+        if let Err(e) = check_free_space(disk, size) {
+            self.error_message = Some(e.to_string());
+            return;
+        }
+        self.transfer_progress = None;
+        self.transfer = Some(Transfer::new(source, size, PathBuf::from(mount_point), kind));
+    }
+
+    fn poll_transfer(&mut self, ctx: &egui::Context) {
+        let Some(transfer) = &self.transfer else {
+            return;
+        };
+        let mut finished = false;
+        while let Some(msg) = transfer.try_recv() {
+            match msg {
+                TransferMessage::Progress(p) => {
+                    self.transfer_progress = Some(p);
+                }
+                TransferMessage::Completed => {
+                    if let Some(root) = &mut self.root_node {
+                        root.remove_descendant(&transfer.source);
+                    }
+                    finished = true;
+                }
+                TransferMessage::Error(e) => {
+                    self.error_message = Some(format!("{} failed: {}", transfer.kind.label(), e));
+                    finished = true;
+                }
+            }
+        }
+        if finished {
+            self.transfer = None;
+            self.transfer_progress = None;
+            self.refresh_disks();
+        } else {
+            ctx.request_repaint();
+        }
+    }
+
+    fn handle_duplicate_action(&mut self, action: duplicates::DuplicateAction) {
+        match action {
+            duplicates::DuplicateAction::TrashOthers { keep, others } => {
+                if self.trash_all(&others) {
+                    self.duplicates.remove_group_of(&keep);
+                }
+            }
+            duplicates::DuplicateAction::HardLinkOthers { keep, others } => {
+                if self.refuse_unless_local("Hard linking") {
+                    return;
+                }
+                for path in &others {
+                    if let Err(e) = replace_with_hard_link(&keep, path) {
+                        self.error_message = Some(format!("{:#}", e));
+                        return;
+                    }
+                }
+                self.duplicates.remove_group_of(&keep);
+            }
+            duplicates::DuplicateAction::Item(action) => self.handle_tree_action(action),
+        }
+    }
+
+    /// Trashes `paths` and drops them from the tree without a rescan.
+    /// Returns false and reports the error if the trash refused.
+    fn trash_all(&mut self, paths: &[PathBuf]) -> bool {
+        // The agent reports back when done, then the tree is updated.
+        if let (Some(session), None) = (&mut self.remote, &self.imported_from) {
+            session.agent.delete(paths.to_vec());
+            return true;
+        }
+        if self.refuse_unless_local("Deleting") {
+            return false;
+        }
+        match trash::delete_all(paths) {
+            Ok(_) => {
+                if let Some(root) = &mut self.root_node {
+                    for path in paths {
+                        root.remove_descendant(path);
+                    }
+                }
+                true
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to delete: {}", e));
+                false
+            }
+        }
+    }
+
+    fn handle_reclaim_action(&mut self, action: reclaim::ReclaimAction) {
+        match action {
+            reclaim::ReclaimAction::Trash(paths) => {
+                self.trash_all(&paths);
+            }
+            reclaim::ReclaimAction::EditRules(path) => {
+                let _ = open::that(path);
+            }
+            reclaim::ReclaimAction::Item(action) => self.handle_tree_action(action),
+        }
+    }
+
+    /// Writes an HTML report of the scan, or of the `subtree` folder in it.
+    fn export_report(&mut self, subtree: Option<PathBuf>) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let Some(node) = subtree.as_deref().map_or(Some(root), |p| root.find(p)) else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export HTML report")
+            .set_file_name(format!("{}-report.html", if node.path.as_os_str().is_empty() { "gate" } else { &node.name }))
+            .add_filter("HTML", &["html"])
+            .save_file()
+        else {
+            return;
+        };
+        if let Err(e) = report::export_html(node, &path) {
+            self.error_message = Some(format!("Export failed: {:#}", e));
+        }
+    }
+
+    /// Writes the whole tree in ncdu's export format.
+    fn export_ncdu(&mut self) {
+        let Some(root) = &self.root_node else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export ncdu JSON")
+            .set_file_name(format!("{}.ncdu.json", if root.path.as_os_str().is_empty() { "gate" } else { &root.name }))
+            .add_filter("ncdu JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        if let Err(e) = ncdu::export(root, &path) {
+            self.error_message = Some(format!("Export failed: {:#}", e));
+        }
+    }
+
+    fn handle_tree_action(&mut self, action: tree::TreeAction) {
+        match action {
+            tree::TreeAction::Delete(path) => self.delete_item(path),
+            tree::TreeAction::Open(path) => {
+                if !self.refuse_unless_local("Opening") {
+                    let _ = open::that(path);
+                }
+            }
+            tree::TreeAction::MoveTo(path) => {
+                if self.transfer.is_none() && !self.refuse_unless_local("Moving") {
+                    self.move_source = Some(path);
+                }
+            }
+        }
+    }
+
+    /// Re-reads mounted filesystems, dropping selected mounts that went away.
+    fn refresh_disks(&mut self) {
+        self.disks = get_disks();
+        self.disks_read_at = Instant::now();
+        let disks = &self.disks;
+        self.disk_picker.selected.retain(|m| disks.iter().any(|d| d.mount_point == *m));
+        let recorded = self.usage_history.record(&storage_devices(&self.disks), chrono::Utc::now().timestamp());
+        if recorded && self.history_error.is_none() {
+            if let Err(e) = self.usage_history.save() {
+                self.error_message = Some(format!("Failed to save usage history: {:#}", e));
+            }
+        }
+        self.check_low_space();
+    }
+
+    /// Notifies about disks that newly dropped below their alert threshold.
+    /// A disk that recovers is forgotten, so a later drop alerts again.
+    fn check_low_space(&mut self) {
+        let devices = storage_devices(&self.disks);
+        let low = self.settings.alerts.low_space(&devices);
+        self.low_space_notified.retain(|m| low.iter().any(|(d, _)| d.mount_point == *m));
+        for (disk, threshold) in low {
+            if self.low_space_notified.insert(disk.mount_point.clone()) && self.settings.alerts.notify {
+                notify_low_space(disk, threshold);
+            }
+        }
+    }
+
     fn go_home(&mut self) {
-        self.selected_disk_mount = None;
+        self.scan_roots.clear();
+        self.imported_from = None;
         self.root_node = None;
         self.is_scanning = false;
         self.scanner = None;
-        self.disks = get_disks();
+        self.duplicates.reset();
+        self.refresh_disks();
     }
 
     fn settings_modal(&mut self, ctx: &egui::Context) {
         let mut is_open = self.show_settings_modal;
         let mut close_requested = false;
+        let mut reset_requested = false;
         
         egui::Window::new("Settings")
             .open(&mut is_open)
             .resizable(false)
             .collapsible(false)
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-            .fixed_size([400.0, 300.0])
+            .fixed_size([420.0, 300.0])
             .show(ctx, |ui| {
                 ui.add_space(10.0);
                 
@@ -91,20 +582,21 @@ impl GateApp {
                     
                     ui.horizontal(|ui| {
                         ui.label("Theme:");
-                        if ui.selectable_label(self.dark_mode, "Dark").clicked() {
-                            self.dark_mode = true;
-                            ctx.set_visuals(egui::Visuals::dark());
+                        let appearance = &mut self.settings.appearance;
+                        if ui.selectable_label(appearance.dark_mode, "Dark").clicked() {
+                            appearance.dark_mode = true;
+                            ctx.set_visuals(visuals(true));
                         }
-                        if ui.selectable_label(!self.dark_mode, "Light").clicked() {
-                            self.dark_mode = false;
-                            ctx.set_visuals(egui::Visuals::light());
+                        if ui.selectable_label(!appearance.dark_mode, "Light").clicked() {
+                            appearance.dark_mode = false;
+                            ctx.set_visuals(visuals(false));
                         }
                     });
                     
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
                         ui.label("UI Scale:");
-                        ui.add(egui::Slider::new(&mut self.ui_scale, 0.8..=2.0).step_by(0.05));
+                        ui.add(egui::Slider::new(&mut self.settings.appearance.ui_scale, 0.8..=2.0).step_by(0.05));
                     });
                 });
                 
@@ -117,35 +609,136 @@ impl GateApp {
                     
                     ui.horizontal(|ui| {
                         ui.label("Default Zoom:");
-                        ui.add(egui::Slider::new(&mut self.zoom_factor, 0.5..=2.0));
+                        if ui.add(egui::Slider::new(&mut self.settings.appearance.zoom, 0.5..=2.0)).changed() {
+                            self.zoom_factor = self.settings.appearance.zoom;
+                        }
                     });
                 });
                 
+                ui.add_space(12.0);
+
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(egui::RichText::new("Scanning").strong());
+                    ui.add_space(8.0);
+
+                    ui.checkbox(&mut self.settings.scan.one_file_system, "Stay on one filesystem");
+                    ui.add_space(4.0);
+                    ui.label("Exclude (glob, e.g. /proc, .snapshot, /var/lib/docker/overlay2):");
+
+                    let mut remove = None;
+                    for (i, pattern) in self.settings.scan.exclusions.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("✕").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.label(egui::RichText::new(pattern).monospace());
+                            if let Err(e) = ScanOptions::check_exclusion(pattern) {
+                                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "⚠ invalid, ignored")
+                                    .on_hover_text(e);
+                            }
+                        });
+                    }
+                    if let Some(i) = remove {
+                        self.settings.scan.exclusions.remove(i);
+                    }
+
+                    ui.horizontal(|ui| {
+                        let edit = ui.add(egui::TextEdit::singleline(&mut self.new_exclusion).desired_width(220.0));
+                        let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        let pattern = self.new_exclusion.trim();
+                        let check = ScanOptions::check_exclusion(pattern);
+                        let valid = !pattern.is_empty() && check.is_ok();
+                        if (ui.add_enabled(valid, egui::Button::new("Add")).clicked() || submitted) && valid {
+                            self.settings.scan.exclusions.push(pattern.to_string());
+                            self.new_exclusion.clear();
+                        }
+                        if let Err(e) = check {
+                            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("Invalid glob: {}", e));
+                        }
+                    });
+                });
+
+                ui.add_space(12.0);
+
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(egui::RichText::new("Low Space Alerts").strong());
+                    ui.add_space(8.0);
+                    self.alert_editor.ui(ui, &mut self.settings.alerts, &storage_devices(&self.disks));
+                });
+                
+                if let Some(e) = &self.settings_error {
+                    ui.add_space(12.0);
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("⚠ {}", e));
+                    ui.label("Changes are not saved until the file is fixed and Gate restarted, or reset to defaults.");
+                }
+
                 ui.add_space(20.0);
-                ui.vertical_centered(|ui| {
-                    if ui.button("Close").clicked() {
-                        close_requested = true;
+                ui.horizontal(|ui| {
+                    if ui.button("Reset to defaults").on_hover_text("Also resets window size and column widths").clicked() {
+                        reset_requested = true;
                     }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            close_requested = true;
+                        }
+                    });
                 });
             });
+
+        if reset_requested {
+            self.settings.reset();
+            self.settings_error = None;
+            ctx.set_visuals(visuals(self.settings.appearance.dark_mode));
+            ctx.memory_mut(|m| m.data.clear());
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(DEFAULT_WINDOW_SIZE.into()));
+            self.zoom_factor = self.settings.appearance.zoom;
+            self.top_files = top_files::TopFilesView::new(&self.settings.top_files);
+            self.tree_view.set_layout(&self.settings.tree);
+        }
             
         self.show_settings_modal = is_open && !close_requested;
+        if !self.show_settings_modal {
+            self.save_settings();
+            self.check_low_space();
+        }
+    }
+
+    fn save_settings(&mut self) {
+        if self.settings_error.is_some() {
+            return;
+        }
+        self.settings.top_files = self.top_files.layout();
+        self.settings.tree = self.tree_view.layout();
+        if let Err(e) = self.settings.save() {
+            self.error_message = Some(format!("Failed to save settings: {:#}", e));
+        }
     }
 }
 
 impl eframe::App for GateApp {
+    /// Called by eframe periodically and on exit, alongside its own window
+    /// state persistence.
+    fn save(&mut self, _storage: &mut dyn eframe::Storage) {
+        self.save_settings();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        ctx.set_pixels_per_point(self.ui_scale);
+        ctx.set_pixels_per_point(self.settings.appearance.ui_scale);
         
         // Handle scanner messages
         let mut scan_finished = false;
-        if let Some(scanner) = &self.scanner {
+        if let Some(scanner) = &mut self.scanner {
             while let Some(msg) = scanner.try_recv() {
                 match msg {
                     ScanMessage::Progress(p) => {
                         self.scan_progress = p;
                     }
                     ScanMessage::Completed(node) => {
+                        if !scanner.failures.is_empty() {
+                            self.error_message = Some(format!("Some locations could not be scanned:\n{}", scanner.failures.join("\n")));
+                        }
                         self.root_node = Some(node);
                         self.is_scanning = false;
                         scan_finished = true;
@@ -161,9 +754,28 @@ impl eframe::App for GateApp {
         }
         if scan_finished {
             self.scanner = None;
+            self.refresh_disks();
+        }
+        self.poll_transfer(ctx);
+        self.poll_remote(ctx);
+        // Mount changes are picked up even when idle; free space only needs
+        // to stay current while the disk picker is open.
+        if self.mount_watcher.changed()
+            || (self.show_disk_modal && self.disks_read_at.elapsed() >= DISK_REFRESH_INTERVAL)
+        {
+            self.refresh_disks();
+        }
+        if self.root_node.is_none() && !self.is_scanning {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        self.duplicates.poll();
+        if self.duplicates.is_running() {
+            ctx.request_repaint();
         }
 
-        if self.selected_disk_mount.is_none() {
+        self.handle_dropped_folders(ctx);
+
+        if self.scan_roots.is_empty() {
             // HOME PAGE
             egui::CentralPanel::default().show(ctx, |ui| {
                 ui.vertical_centered(|ui| {
@@ -174,10 +786,11 @@ impl eframe::App for GateApp {
                     
                     ui.add_space(60.0);
                     
+                    let mut open_paths = None;
                     ui.horizontal(|ui| {
                         let btn_width = 160.0;
                         let btn_height = 48.0;
-                        let total_width = (btn_width * 2.0) + 20.0;
+                        let total_width = (btn_width * 3.0) + 40.0;
                         ui.add_space((ui.available_width() - total_width) / 2.0);
                         
                         let btn_disk = egui::Button::new(egui::RichText::new("🖴 Select Disk").size(16.0))
@@ -188,6 +801,16 @@ impl eframe::App for GateApp {
                             self.show_disk_modal = true;
                         }
                         
+                        ui.add_space(20.0);
+
+                        let btn_folder = egui::Button::new(egui::RichText::new("📂 Open Folder").size(16.0))
+                            .min_size(egui::vec2(btn_width, btn_height))
+                            .rounding(egui::Rounding::same(10.0));
+
+                        if ui.add(btn_folder).clicked() {
+                            open_paths = Self::pick_folders();
+                        }
+
                         ui.add_space(20.0);
                         
                         let btn_settings = egui::Button::new(egui::RichText::new("⚙ Settings").size(16.0))
@@ -198,19 +821,92 @@ impl eframe::App for GateApp {
                             self.show_settings_modal = true;
                         }
                     });
+
+                    ui.add_space(30.0);
+                    if let Some(path) = self.location_input.ui(ui, 520.0) {
+                        open_paths = Some(vec![path]);
+                    }
+                    if !self.settings.last_roots.is_empty() {
+                        ui.add_space(14.0);
+                        let label = match self.settings.last_roots.as_slice() {
+                            [root] => format!("↻ Reopen {}", root),
+                            roots => format!("↻ Reopen last session ({} locations)", roots.len()),
+                        };
+                        if ui.button(label).on_hover_text(self.settings.last_roots.join("\n")).clicked() {
+                            open_paths = Some(self.settings.last_roots.iter().map(PathBuf::from).collect());
+                        }
+                    }
+                    ui.add_space(8.0);
+                    if let Some(session) = &self.remote {
+                        let host = session.host();
+                        ui.horizontal(|ui| {
+                            ui.add_space((ui.available_width() - 380.0).max(0.0) / 2.0);
+                            ui.label(egui::RichText::new(format!("🖧 Connected to {}", host)).strong());
+                            if ui.button("Choose disks").clicked() {
+                                self.show_disk_modal = true;
+                            }
+                            if ui.button("Disconnect").clicked() {
+                                self.remote = None;
+                            }
+                        });
+                    } else if ui.button("🖧 Scan a remote host…").on_hover_text("Run Gate on a server over SSH").clicked() {
+                        self.show_remote_modal = true;
+                    }
+                    ui.add_space(4.0);
+                    if ui.button("📥 Import ncdu export…").on_hover_text("Browse a scan made with `ncdu -o`").clicked() {
+                        if let Some(file) = Self::pick_ncdu_export() {
+                            self.import_ncdu(file);
+                        }
+                    }
+                    ui.add_space(20.0);
+                    if let Some(path) = location::recent_locations_ui(ui, &self.settings.recent_locations) {
+                        open_paths = Some(vec![path]);
+                    }
+                    ui.add_space(10.0);
+                    ui.label(egui::RichText::new("You can also drop a folder onto this window.").small().weak());
+
+                    if let Some(paths) = open_paths {
+                        self.open_locations(paths);
+                    }
                 });
             });
             
             // Modals
             if self.show_disk_modal {
-                if disk_select::disk_modal_ui(ctx, &self.disks, &mut self.selected_disk_mount, &mut self.show_disk_modal) {
-                    self.start_scan();
+                // Usage history is only recorded for this machine's disks.
+                let no_history = UsageHistory::default();
+                let (disks, host, history, picker) = match &mut self.remote {
+                    Some(session) => (&session.disks, Some(session.host()), &no_history, &mut session.picker),
+                    None => (&self.disks, None, &self.usage_history, &mut self.disk_picker),
+                };
+                match disk_select::disk_modal_ui(ctx, disks, host.as_deref(), history, &self.settings.alerts, picker, &mut self.show_disk_modal) {
+                    Some(disk_select::DiskModalAction::Scan) => {
+                        let mounts = picker.selected.iter().map(PathBuf::from).collect();
+                        if self.remote.is_some() {
+                            self.open_remote_locations(mounts);
+                        } else {
+                            self.open_locations(mounts);
+                        }
+                    }
+                    Some(disk_select::DiskModalAction::Refresh) => match &mut self.remote {
+                        Some(session) => session.refresh_disks(),
+                        None => self.refresh_disks(),
+                    },
+                    Some(disk_select::DiskModalAction::ShowHistory(mount)) => self.history_mount = Some(mount),
+                    None => {}
                 }
             }
+            history::usage_history_window(ctx, &self.usage_history, &mut self.history_mount);
             
             if self.show_settings_modal {
                 self.settings_modal(ctx);
             }
+
+            if self.show_remote_modal {
+                if let Some(transport) = self.remote_form.ui(ctx, &mut self.settings.remote, &mut self.show_remote_modal) {
+                    self.connect_remote(transport);
+                }
+            }
             
         } else {
             // APP FOOTER
@@ -224,12 +920,25 @@ impl eframe::App for GateApp {
                         ui.label(format!("Total Files: {}", self.scan_progress.files_scanned));
                         ui.separator();
                         ui.label(format!("Total Size: {}", format_size(root.size, DECIMAL)));
+                        ui.separator();
+                        ui.label(format!("Items: {} files, {} folders", root.files, root.dirs));
                     }
                     
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.add_space(10.0);
                         ui.add(egui::Slider::new(&mut self.zoom_factor, 0.5..=2.0).text("Tree Zoom").show_value(true));
                         ui.separator();
+                        let mut measure = self.settings.appearance.measure;
+                        for option in [Measure::Items, Measure::Bytes] {
+                            ui.selectable_value(&mut measure, option, option.label());
+                        }
+                        ui.label(egui::RichText::new("Compare").weak())
+                            .on_hover_text("What bars, percentages and charts compare: bytes, or the number of files and folders");
+                        if measure != self.settings.appearance.measure {
+                            self.settings.appearance.measure = measure;
+                            self.save_settings();
+                        }
+                        ui.separator();
                         if let Some(path) = &self.tree_view.selected_path {
                             ui.label(egui::RichText::new(path.to_string_lossy()).small().weak());
                         }
@@ -238,6 +947,8 @@ impl eframe::App for GateApp {
             });
 
             // MAIN APP VIEW
+            let mut export = None;
+            let mut export_ncdu = false;
             egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
                 ui.add_space(6.0);
                 ui.horizontal(|ui| {
@@ -252,15 +963,47 @@ impl eframe::App for GateApp {
                     
                     ui.separator();
                     
-                    if let Some(mount) = &self.selected_disk_mount {
-                        if let Some(disk) = self.disks.iter().find(|d| &d.mount_point == mount) {
-                            ui.horizontal(|ui| {
-                                ui.label(egui::RichText::new(&disk.name).strong());
-                                ui.label(egui::RichText::new(format!("({})", disk.mount_point)).weak().small());
-                            });
+                    if let [root] = self.scan_roots.as_slice() {
+                        let disk = self.scan_disks().iter()
+                            .find(|d| self.imported_from.is_none() && Path::new(&d.mount_point) == root);
+                        let name = match disk {
+                            Some(disk) => disk.name.clone(),
+                            None => root.file_name().unwrap_or(root.as_os_str()).to_string_lossy().to_string(),
+                        };
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(name).strong());
+                            ui.label(egui::RichText::new(format!("({})", root.display())).weak().small());
+                            if let Some(file) = &self.imported_from {
+                                ui.label(egui::RichText::new("ncdu import").small().color(egui::Color32::from_rgb(230, 180, 80)))
+                                    .on_hover_text(format!("Read-only, loaded from {}", file.display()));
+                            }
+                        });
+                    } else {
+                        let list: Vec<String> = self.scan_roots.iter().map(|r| r.display().to_string()).collect();
+                        ui.label(egui::RichText::new(format!("{} locations", self.scan_roots.len())).strong())
+                            .on_hover_text(list.join("\n"));
+                    }
+                    if let (Some(session), None) = (&self.remote, &self.imported_from) {
+                        ui.label(egui::RichText::new(format!("🖧 {}", session.host())).small().color(egui::Color32::from_rgb(120, 170, 255)))
+                            .on_hover_text("Scanned on the remote host; deleting moves items to its trash");
+                    }
+
+                    let add_btn = egui::Button::new(egui::RichText::new("➕").size(13.0))
+                        .min_size(egui::vec2(26.0, 26.0));
+                    let local = self.imported_from.is_none() && self.remote.is_none();
+                    if ui.add_enabled(!self.is_scanning && local, add_btn).on_hover_text("Add folders to this view").clicked() {
+                        if let Some(paths) = Self::pick_folders() {
+                            let mut roots = self.scan_roots.clone();
+                            roots.extend(paths);
+                            self.open_locations(roots);
                         }
                     }
 
+                    ui.separator();
+                    for tab in ViewTab::ALL {
+                        ui.selectable_value(&mut self.active_tab, tab, tab.label());
+                    }
+
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.add_space(8.0);
                         
@@ -279,8 +1022,36 @@ impl eframe::App for GateApp {
                         if ui.add(rescan_btn).clicked() {
                             self.start_scan();
                         }
+
+                        ui.add_space(4.0);
+                        ui.menu_button("⤓ Export", |ui| {
+                            if ui.button("HTML report").clicked() {
+                                export = Some(None);
+                                ui.close_menu();
+                            }
+                            let selected_dir = self.tree_view.selected_path.clone()
+                                .filter(|p| self.root_node.as_ref().and_then(|r| r.find(p)).is_some_and(|n| n.is_dir));
+                            if ui.add_enabled(selected_dir.is_some(), egui::Button::new("HTML report of selected folder")).clicked() {
+                                export = Some(selected_dir);
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if ui.button("ncdu JSON").clicked() {
+                                export_ncdu = true;
+                                ui.close_menu();
+                            }
+                        });
                         
                         ui.separator();
+                        let mut clear_filter = false;
+                        if let Some(filter) = &self.tree_view.type_filter {
+                            if ui.button(format!("✕ {}", filter.label())).on_hover_text("Clear type filter").clicked() {
+                                clear_filter = true;
+                            }
+                        }
+                        if clear_filter {
+                            self.tree_view.type_filter = None;
+                        }
                         egui::Frame::none()
                             .fill(egui::Color32::from_black_alpha(100))
                             .rounding(egui::Rounding::same(6.0))
@@ -291,21 +1062,55 @@ impl eframe::App for GateApp {
                                     ui.label(egui::RichText::new("🔍").size(14.0));
                                     ui.add(
                                         egui::TextEdit::singleline(&mut self.tree_view.search_query)
-                                            .hint_text("Search...")
+                                            .id(tree::search_field_id())
+                                            .hint_text("Search... (ext:mp4 size:>1G)")
                                             .frame(false)
-                                            .desired_width(150.0)
-                                    );
+                                            .desired_width(220.0)
+                                    ).on_hover_text(SEARCH_HELP);
                                 });
                             });
+                        if let Some(err) = &self.tree_view.search_error {
+                            ui.label(egui::RichText::new("⚠").color(egui::Color32::from_rgb(255, 100, 100)))
+                                .on_hover_text(err);
+                        }
+                        if let Some(count) = self.tree_view.match_count() {
+                            ui.checkbox(&mut self.tree_view.flatten_results, "Flatten");
+                            ui.label(egui::RichText::new(format!("{} matches", count)).weak());
+                        }
                     });
                 });
                 ui.add_space(6.0);
             });
 
+            if let Some(subtree) = export {
+                self.export_report(subtree);
+            }
+            if export_ncdu {
+                self.export_ncdu();
+            }
+
             if self.show_settings_modal {
                 self.settings_modal(ctx);
             }
 
+            if let Some(source) = self.move_source.clone() {
+                let size = self.root_node.as_ref().and_then(|r| r.find(&source)).map_or(0, |n| n.size);
+                let source_mount = disk_for_path(&self.disks, &source).map(|d| d.mount_point.clone());
+                match transfer::move_modal_ui(ctx, &source, size, &self.disks, source_mount.as_deref(), &mut self.move_kind) {
+                    Some(transfer::MoveDialogAction::Start { mount_point, kind }) => {
+                        self.move_source = None;
+                        self.start_transfer(source, mount_point, kind);
+                    }
+                    Some(transfer::MoveDialogAction::Cancel) => self.move_source = None,
+                    None => {}
+                }
+            }
+
+            if let Some(t) = &self.transfer {
+                let progress = self.transfer_progress.clone().unwrap_or_else(|| TransferProgress::new(0));
+                transfer::transfer_progress_ui(ctx, &t.source, t.kind, &progress);
+            }
+
             egui::CentralPanel::default().show(ctx, |ui| {
                 if self.is_scanning {
                     ui.centered_and_justified(|ui| {
@@ -350,18 +1155,24 @@ impl eframe::App for GateApp {
                             
                             ui.add_space(30.0);
                             
-                            // Calculate real progress based on used space
-                            let used_space = self.disks.iter()
-                                .find(|d| Some(&d.mount_point) == self.selected_disk_mount.as_ref())
-                                .map(|d| d.total_space - d.available_space)
-                                .unwrap_or(1);
-                            
-                            let progress_ratio = (self.scan_progress.bytes_scanned as f32 / used_space as f32).min(0.99);
+                            // Calculate real progress based on used space; a folder
+                            // inside a disk has no known total, so only animate.
+                            let used_space = self.scan_roots.iter()
+                                .map(|root| self.scan_disks().iter()
+                                    .find(|d| Path::new(&d.mount_point) == root)
+                                    .map(|d| d.total_space - d.available_space))
+                                .sum::<Option<u64>>();
                             
-                            let pb = egui::ProgressBar::new(progress_ratio)
+                            let pb = match used_space {
+                                Some(used_space) => {
+                                    let progress_ratio = (self.scan_progress.bytes_scanned as f32 / used_space.max(1) as f32).min(0.99);
+                                    egui::ProgressBar::new(progress_ratio)
+                                        .text(format!("{:.1}%", progress_ratio * 100.0))
+                                }
+                                None => egui::ProgressBar::new(0.0).text(format_size(self.scan_progress.bytes_scanned, DECIMAL)),
+                            }
                                 .animate(true)
-                                .rounding(egui::Rounding::same(6.0))
-                                .text(format!("{:.1}%", progress_ratio * 100.0));
+                                .rounding(egui::Rounding::same(6.0));
                             
                             ui.add_sized([450.0, 24.0], pb);
                             ui.add_space(10.0);
@@ -369,29 +1180,62 @@ impl eframe::App for GateApp {
                         });
                     });
                 } else if let Some(root) = &mut self.root_node {
+                    let local = self.imported_from.is_none() && self.remote.is_none();
                     let mut tree_action = None;
+                    let mut duplicate_action = None;
+                    let mut reclaim_action = None;
                     
-                    egui::ScrollArea::vertical()
-                        .id_source("main_tree_scroll")
-                        .auto_shrink([false; 2])
-                        .show(ui, |ui| {
-                            ui.set_max_width(ui.available_width());
-                            if let Some(action) = self.tree_view.ui_zoomed(ui, root, self.zoom_factor) {
-                                tree_action = Some(action);
+                    match self.active_tab {
+                        ViewTab::Tree => {
+                            self.tree_view.update_search(root);
+                            if self.tree_view.is_flattened() {
+                                tree_action = self.tree_view.flat_results_ui(ui, root);
+                            } else {
+                                tree_action = self.tree_view.navigation_ui(ui, root);
+                                self.tree_view.resolve_owners = local;
+                                self.tree_view.set_measure(self.settings.appearance.measure);
+                                if let Some(action) = self.tree_view.ui_zoomed(ui, root, self.zoom_factor) {
+                                    tree_action = Some(action);
+                                }
                             }
-                        });
-
-                    if let Some(action) = tree_action {
-                        match action {
-                            tree::TreeAction::Delete(path) => self.delete_item(path),
-                            tree::TreeAction::Open(path) => {
-                                let _ = open::that(path);
+                        }
+                        ViewTab::TopFiles => {
+                            tree_action = self.top_files.ui(ui, root, &mut self.tree_view.selected_path);
+                        }
+                        ViewTab::Types => {
+                            if let Some(filter) = self.type_breakdown.ui(ui, root, self.settings.appearance.measure) {
+                                self.tree_view.type_filter = Some(filter);
+                                self.active_tab = ViewTab::Tree;
                             }
                         }
+                        ViewTab::Age => {
+                            tree_action = self.age_view.ui(ui, root, self.settings.appearance.measure, &mut self.tree_view.selected_path);
+                        }
+                        ViewTab::Duplicates if !local => {
+                            ui.centered_and_justified(|ui| {
+                                ui.label("Finding duplicates compares file contents, which is only possible for scans of this machine.");
+                            });
+                        }
+                        ViewTab::Duplicates => {
+                            duplicate_action = self.duplicates.ui(ui, root);
+                        }
+                        ViewTab::Reclaim => {
+                            reclaim_action = self.reclaim.ui(ui, root);
+                        }
+                    }
+
+                    if let Some(action) = tree_action {
+                        self.handle_tree_action(action);
+                    }
+                    if let Some(action) = duplicate_action {
+                        self.handle_duplicate_action(action);
+                    }
+                    if let Some(action) = reclaim_action {
+                        self.handle_reclaim_action(action);
                     }
                 } else {
                     ui.centered_and_justified(|ui| {
-                        ui.label("No data. Select a disk to start.");
+                        ui.label("No data. Select a disk or folder to start.");
                     });
                 }
             });
@@ -412,3 +1256,21 @@ impl eframe::App for GateApp {
     }
 }
 
+fn visuals(dark_mode: bool) -> egui::Visuals {
+    if dark_mode { egui::Visuals::dark() } else { egui::Visuals::light() }
+}
+
+/// Drops locations nested inside another one so nothing is counted twice,
+/// unless `separate(outer, inner)` says the outer scan will not cover the
+/// inner one, as with a mount below a root scanned on one filesystem.
+fn distinct_roots(paths: Vec<PathBuf>, separate: impl Fn(&Path, &Path) -> bool) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if roots.iter().any(|r| path.starts_with(r) && !separate(r, &path)) {
+            continue;
+        }
+        roots.retain(|r| !r.starts_with(&path) || separate(&path, r));
+        roots.push(path);
+    }
+    roots
+}
@@ -0,0 +1,279 @@
+use crate::core::disk::{get_disks, storage_devices};
+use crate::core::ncdu;
+use crate::core::remote::{self, RemoteAgent, RemoteMessage, Transport};
+use crate::core::report::export_html;
+use crate::core::rules::{distinct_paths, RuleSet};
+use crate::core::scanner::{FileNode, MultiScanner, ScanMessage};
+use crate::core::settings::Settings;
+use anyhow::{anyhow, bail, Context, Result};
+use humansize::{format_size, DECIMAL};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+const USAGE: &str = "\
+Usage: gate [COMMAND]
+       gate [FOLDER...]
+
+Without a command, starts the graphical interface, scanning FOLDERs if given.
+
+Commands:
+  check-space [--all]   Check free space against the alert thresholds from
+                        settings. Exits with 1 if any disk is below its
+                        threshold. --all includes virtual filesystems.
+  clean [--rules FILE] [--apply] PATH...
+                        Scan PATHs and report what the cleanup rules match
+                        (default rules: rules.toml in the config directory).
+                        --apply moves the matches to the trash.
+  report [-o FILE] PATH...
+  report [-o FILE] --ncdu EXPORT
+                        Scan PATHs, or read an `ncdu -o` export, and write a
+                        self-contained HTML report (default: gate-report.html).
+  ncdu-export [-o FILE] PATH...
+                        Scan PATHs and write them in ncdu's JSON export format
+                        (default: gate.ncdu.json).
+  remote [--command CMD] (HOST | --loopback) [PATH...]
+                        Connect to Gate on HOST over SSH and list its disks,
+                        or scan PATHs there and print the largest entries.
+                        CMD is how Gate is started there (default: gate);
+                        --loopback talks to a local agent instead.
+  agent                 Serve scans over stdin and stdout. Started by
+                        `remote` and the GUI through SSH.
+  help                  Show this help.
+
+Exit codes: 0 success, 1 check failed, 2 usage or runtime error.";
+
+const COMMANDS: &[&str] = &["check-space", "clean", "report", "ncdu-export", "remote", "agent", "help", "-h", "--help"];
+
+/// Whether `arg` names a headless command. Anything else starts the GUI, so
+/// opening a folder with Gate from a file manager still works.
+pub fn is_command(arg: &str) -> bool {
+    COMMANDS.contains(&arg)
+}
+
+/// Headless entry point, used when Gate is started with a command.
+pub fn run(args: &[String]) -> ExitCode {
+    let result = match args[0].as_str() {
+        "check-space" => check_space(&args[1..]),
+        "clean" => clean(&args[1..]),
+        "report" => report(&args[1..]),
+        "ncdu-export" => ncdu_export(&args[1..]),
+        "remote" => remote_scan(&args[1..]),
+        "agent" => remote::serve(std::io::stdin().lock(), std::io::stdout().lock()).map(|_| true),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        other => {
+            eprintln!("Unknown command '{}'\n\n{}", other, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("gate: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Returns false if any disk is below its threshold.
+fn check_space(args: &[String]) -> Result<bool> {
+    let mut all = false;
+    for arg in args {
+        match arg.as_str() {
+            "--all" => all = true,
+            other => bail!("Unexpected argument '{}'", other),
+        }
+    }
+
+    let settings = Settings::load();
+    let disks = get_disks();
+    let disks = if all { disks } else { storage_devices(&disks) };
+    let mut ok = true;
+    for disk in &disks {
+        let threshold = settings.alerts.threshold_for(&disk.mount_point);
+        let low = threshold.is_some_and(|t| t.is_crossed(disk));
+        ok &= !low;
+        println!(
+            "{:<4} {:<24} {:>10} free of {:>10}  {}",
+            if low { "LOW" } else { "OK" },
+            disk.mount_point,
+            format_size(disk.available_space, DECIMAL),
+            format_size(disk.total_space, DECIMAL),
+            threshold.map_or("(no threshold)".to_string(), |t| format!("(threshold: {})", t.label())),
+        );
+    }
+    Ok(ok)
+}
+
+/// Scans `paths` with the saved scan options and waits for the result.
+fn scan(paths: Vec<PathBuf>, settings: &Settings) -> Result<FileNode> {
+    let mut scanner = MultiScanner::new(paths, settings.scan.clone());
+    loop {
+        match scanner.try_recv() {
+            Some(ScanMessage::Completed(root)) => {
+                for failure in &scanner.failures {
+                    eprintln!("gate: could not scan {}", failure);
+                }
+                return Ok(root);
+            }
+            Some(ScanMessage::Error(e)) => return Err(anyhow!(e)),
+            Some(ScanMessage::Progress(_)) => {}
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+fn clean(args: &[String]) -> Result<bool> {
+    let mut rules_path = None;
+    let mut apply = false;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" => rules_path = Some(PathBuf::from(args.next().context("--rules needs a file")?)),
+            "--apply" => apply = true,
+            other if other.starts_with("--") => bail!("Unexpected argument '{}'", other),
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+    if paths.is_empty() {
+        bail!("clean needs at least one PATH");
+    }
+
+    let rules = match &rules_path {
+        Some(path) => RuleSet::load(path)?,
+        None => RuleSet::load_default()?,
+    };
+    if rules.rules.is_empty() {
+        bail!("No cleanup rules defined");
+    }
+    let settings = Settings::load();
+    let root = scan(paths, &settings)?;
+    let matches = rules.evaluate(&root, SystemTime::now())?;
+
+    let mut total = 0;
+    for rule_match in &matches {
+        println!("{} ({}, {} matches)", rule_match.rule.name, format_size(rule_match.size, DECIMAL), rule_match.paths.len());
+        for path in &rule_match.paths {
+            println!("  {}", path.display());
+        }
+        total += rule_match.size;
+    }
+    println!("Total reclaimable: {}", format_size(total, DECIMAL));
+
+    if apply && !matches.is_empty() {
+        let paths = distinct_paths(&matches);
+        trash::delete_all(&paths).context("Failed to move matches to the trash")?;
+        println!("Moved {} items to the trash", paths.len());
+    }
+    Ok(true)
+}
+
+fn report(args: &[String]) -> Result<bool> {
+    let mut output = PathBuf::from("gate-report.html");
+    let mut import = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = PathBuf::from(args.next().context("-o needs a file")?),
+            "--ncdu" => import = Some(PathBuf::from(args.next().context("--ncdu needs a file")?)),
+            other if other.starts_with('-') => bail!("Unexpected argument '{}'", other),
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+
+    let root = match import {
+        Some(file) if paths.is_empty() => ncdu::import(&file)?,
+        Some(_) => bail!("report takes either PATHs or --ncdu, not both"),
+        None if paths.is_empty() => bail!("report needs at least one PATH"),
+        None => scan(paths, &Settings::load())?,
+    };
+    export_html(&root, &output)?;
+    println!("Wrote {} ({} scanned)", output.display(), format_size(root.size, DECIMAL));
+    Ok(true)
+}
+
+fn ncdu_export(args: &[String]) -> Result<bool> {
+    let mut output = PathBuf::from("gate.ncdu.json");
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = PathBuf::from(args.next().context("-o needs a file")?),
+            other if other.starts_with('-') => bail!("Unexpected argument '{}'", other),
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+    if paths.is_empty() {
+        bail!("ncdu-export needs at least one PATH");
+    }
+
+    let settings = Settings::load();
+    let root = scan(paths, &settings)?;
+    ncdu::export(&root, &output)?;
+    println!("Wrote {} ({} scanned)", output.display(), format_size(root.size, DECIMAL));
+    Ok(true)
+}
+
+fn remote_scan(args: &[String]) -> Result<bool> {
+    let mut command = Settings::load().remote.command;
+    let mut loopback = false;
+    let mut host = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--command" => command = args.next().context("--command needs a value")?.clone(),
+            "--loopback" => loopback = true,
+            other if other.starts_with('-') => bail!("Unexpected argument '{}'", other),
+            other if host.is_none() && !loopback => host = Some(other.to_string()),
+            path => paths.push(PathBuf::from(path)),
+        }
+    }
+    let transport = match host {
+        _ if loopback => Transport::Loopback,
+        Some(host) => Transport::Ssh { host, command },
+        None => bail!("remote needs a HOST or --loopback"),
+    };
+
+    let scanning = !paths.is_empty();
+    let mut agent = RemoteAgent::connect(transport)?;
+    if scanning {
+        agent.scan(paths, Settings::load().scan);
+    } else {
+        agent.request_disks();
+    }
+    loop {
+        match agent.try_recv() {
+            Some(RemoteMessage::Disks(disks)) => {
+                for disk in storage_devices(&disks) {
+                    println!(
+                        "{:<24} {:>10} free of {:>10}  {}",
+                        disk.mount_point,
+                        format_size(disk.available_space, DECIMAL),
+                        format_size(disk.total_space, DECIMAL),
+                        disk.file_system,
+                    );
+                }
+                return Ok(true);
+            }
+            Some(RemoteMessage::Scan(ScanMessage::Completed(root))) => {
+                println!("{:>10}  {}", format_size(root.size, DECIMAL), root.path.display());
+                let mut children: Vec<&FileNode> = root.children.iter().collect();
+                children.sort_by_key(|c| std::cmp::Reverse(c.size));
+                for child in children.into_iter().take(20) {
+                    println!("{:>10}  {}", format_size(child.size, DECIMAL), child.path.display());
+                }
+                return Ok(true);
+            }
+            Some(RemoteMessage::Error(e) | RemoteMessage::Disconnected(e)) => bail!(e),
+            Some(_) => {}
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
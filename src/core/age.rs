@@ -0,0 +1,88 @@
+use crate::core::scanner::FileNode;
+use chrono::{DateTime, Local};
+use std::time::{Duration, SystemTime};
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgeField {
+    Modified,
+    Accessed,
+}
+
+impl AgeField {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AgeField::Modified => "Modified",
+            AgeField::Accessed => "Accessed",
+        }
+    }
+
+    pub fn of(&self, node: &FileNode) -> Option<SystemTime> {
+        match self {
+            AgeField::Modified => node.modified,
+            AgeField::Accessed => node.accessed,
+        }
+    }
+}
+
+/// Upper bounds of the histogram buckets in days; the last bucket is open ended.
+pub const BUCKETS: [(u64, &str); 7] = [
+    (7, "< 1 week"),
+    (30, "< 1 month"),
+    (90, "< 3 months"),
+    (180, "< 6 months"),
+    (365, "< 1 year"),
+    (730, "< 2 years"),
+    (1825, "< 5 years"),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct AgeBucket {
+    pub label: &'static str,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// Bytes and file counts by age, oldest bucket last, plus files without a timestamp.
+pub fn age_histogram(root: &FileNode, field: AgeField, now: SystemTime) -> Vec<AgeBucket> {
+    let mut buckets: Vec<AgeBucket> = BUCKETS.iter()
+        .map(|(_, label)| AgeBucket { label, ..Default::default() })
+        .collect();
+    buckets.push(AgeBucket { label: "≥ 5 years", ..Default::default() });
+    buckets.push(AgeBucket { label: "Unknown", ..Default::default() });
+    let unknown = buckets.len() - 1;
+
+    root.visit_files(&mut |file| {
+        let idx = match age_days(field.of(file), now) {
+            Some(days) => BUCKETS.iter().position(|(max, _)| days < *max).unwrap_or(BUCKETS.len()),
+            None => unknown,
+        };
+        buckets[idx].bytes += file.size;
+        buckets[idx].files += 1;
+    });
+    buckets
+}
+
+/// Files whose `field` timestamp is older than `min_age_days` and that are at least `min_size` bytes.
+pub fn stale_files(root: &FileNode, field: AgeField, min_age_days: u64, min_size: u64, now: SystemTime) -> Vec<&FileNode> {
+    let mut files = Vec::new();
+    root.visit_files(&mut |file| {
+        if file.size >= min_size && age_days(field.of(file), now).is_some_and(|d| d >= min_age_days) {
+            files.push(file);
+        }
+    });
+    files.sort_by_key(|f| std::cmp::Reverse(f.size));
+    files
+}
+
+/// Whole days between `time` and `now`.
+pub fn age_days(time: Option<SystemTime>, now: SystemTime) -> Option<u64> {
+    let age = now.duration_since(time?).unwrap_or(Duration::ZERO);
+    Some(age.as_secs() / DAY)
+}
+
+pub fn format_time(time: Option<SystemTime>) -> String {
+    time.map(|t| DateTime::<Local>::from(t).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "—".to_string())
+}
@@ -0,0 +1,96 @@
+use crate::core::disk::DiskInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Minimum free space a mount should keep.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Threshold {
+    /// Percentage of capacity, e.g. `10.0` for 10%.
+    FreePercent(f32),
+    FreeBytes(u64),
+}
+
+impl Threshold {
+    pub fn is_crossed(&self, disk: &DiskInfo) -> bool {
+        if disk.total_space == 0 {
+            return false;
+        }
+        match *self {
+            Threshold::FreePercent(percent) => {
+                (disk.available_space as f64) < disk.total_space as f64 * percent as f64 / 100.0
+            }
+            Threshold::FreeBytes(bytes) => disk.available_space < bytes,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match *self {
+            Threshold::FreePercent(percent) => format!("{}% free", percent),
+            Threshold::FreeBytes(bytes) => format!("{} free", humansize::format_size(bytes, humansize::DECIMAL)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    /// Applies to every mount without an override.
+    pub default: Threshold,
+    /// Per-mount-point overrides.
+    pub mounts: BTreeMap<String, Threshold>,
+    /// Raise a desktop notification when a mount first drops below its threshold.
+    pub notify: bool,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default: Threshold::FreePercent(10.0),
+            mounts: BTreeMap::new(),
+            notify: true,
+        }
+    }
+}
+
+impl AlertSettings {
+    pub fn threshold_for(&self, mount_point: &str) -> Option<Threshold> {
+        if !self.enabled {
+            return None;
+        }
+        Some(self.mounts.get(mount_point).copied().unwrap_or(self.default))
+    }
+
+    pub fn is_low(&self, disk: &DiskInfo) -> bool {
+        self.threshold_for(&disk.mount_point).is_some_and(|t| t.is_crossed(disk))
+    }
+
+    /// Disks below their threshold, with the threshold they crossed.
+    pub fn low_space<'a>(&self, disks: &'a [DiskInfo]) -> Vec<(&'a DiskInfo, Threshold)> {
+        disks.iter()
+            .filter_map(|d| self.threshold_for(&d.mount_point).filter(|t| t.is_crossed(d)).map(|t| (d, t)))
+            .collect()
+    }
+}
+
+/// Sends a freedesktop notification over D-Bus. Runs on its own thread since
+/// the call blocks until the notification daemon answers.
+pub fn notify_low_space(disk: &DiskInfo, threshold: Threshold) {
+    let summary = format!("Low disk space on {}", disk.mount_point);
+    let body = format!(
+        "{} free of {} (threshold: {})",
+        humansize::format_size(disk.available_space, humansize::DECIMAL),
+        humansize::format_size(disk.total_space, humansize::DECIMAL),
+        threshold.label(),
+    );
+    std::thread::spawn(move || {
+        let _ = notify_rust::Notification::new()
+            .appname("Gate")
+            .summary(&summary)
+            .body(&body)
+            .icon("drive-harddisk")
+            .show();
+    });
+}
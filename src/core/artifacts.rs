@@ -0,0 +1,186 @@
+use crate::core::scanner::FileNode;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Regenerable build output, each recognised by a marker file rather than by
+/// its directory name alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArtifactKind {
+    CargoTarget,
+    NodeModules,
+    PythonVenv,
+    PyCache,
+    GradleCache,
+    GradleBuild,
+    CMakeBuild,
+}
+
+impl ArtifactKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArtifactKind::CargoTarget => "Cargo target",
+            ArtifactKind::NodeModules => "node_modules",
+            ArtifactKind::PythonVenv => "Python venv",
+            ArtifactKind::PyCache => "__pycache__",
+            ArtifactKind::GradleCache => ".gradle",
+            ArtifactKind::GradleBuild => "Gradle build",
+            ArtifactKind::CMakeBuild => "CMake build",
+        }
+    }
+
+    /// How to get it back after deleting it.
+    pub fn regenerate_hint(&self) -> &'static str {
+        match self {
+            ArtifactKind::CargoTarget => "Rebuilt by cargo build",
+            ArtifactKind::NodeModules => "Restored by npm/yarn/pnpm install",
+            ArtifactKind::PythonVenv => "Recreate with python -m venv and reinstall packages",
+            ArtifactKind::PyCache => "Recreated automatically by Python",
+            ArtifactKind::GradleCache | ArtifactKind::GradleBuild => "Rebuilt by the next Gradle build",
+            ArtifactKind::CMakeBuild => "Reconfigure and rebuild with CMake",
+        }
+    }
+
+    /// Identifies `dir` given its parent's children (siblings) and its own.
+    fn detect(dir: &FileNode, siblings: &[FileNode]) -> Option<ArtifactKind> {
+        let has_sibling = |name: &str| siblings.iter().any(|s| !s.is_dir && s.name == name);
+        let has_child = |name: &str| dir.children.iter().any(|c| c.name == name);
+        let gradle_project = || {
+            ["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"].iter().any(|m| has_sibling(m))
+        };
+        match dir.name.as_str() {
+            "target" if has_sibling("Cargo.toml") => Some(ArtifactKind::CargoTarget),
+            "node_modules" if has_sibling("package.json") => Some(ArtifactKind::NodeModules),
+            "__pycache__" if dir.children.iter().any(|c| c.extension().as_deref() == Some("pyc")) => {
+                Some(ArtifactKind::PyCache)
+            }
+            ".gradle" if gradle_project() => Some(ArtifactKind::GradleCache),
+            "build" if gradle_project() => Some(ArtifactKind::GradleBuild),
+            _ if has_child("pyvenv.cfg") && has_venv_python(dir) => Some(ArtifactKind::PythonVenv),
+            _ if has_child("CMakeCache.txt") && is_out_of_source_build(dir) => Some(ArtifactKind::CMakeBuild),
+            _ => None,
+        }
+    }
+}
+
+/// A venv has its interpreter next to `pyvenv.cfg`; the config file alone
+/// could be anything.
+fn has_venv_python(dir: &FileNode) -> bool {
+    let has = |subdir: &str, file: &str| {
+        dir.children.iter()
+            .any(|c| c.is_dir && c.name == subdir && c.children.iter().any(|f| f.name == file))
+    };
+    has("bin", "python") || has("Scripts", "python.exe")
+}
+
+/// A CMake build directory kept apart from its sources. An in-source build
+/// has its cache next to `CMakeLists.txt`, and deleting that directory
+/// would delete the project, so only a directory whose cache points at a
+/// source directory elsewhere qualifies.
+fn is_out_of_source_build(dir: &FileNode) -> bool {
+    if dir.children.iter().any(|c| c.name == "CMakeLists.txt") {
+        return false;
+    }
+    cmake_source_dir(&dir.path.join("CMakeCache.txt"))
+        .is_some_and(|source| !source.starts_with(&dir.path))
+}
+
+/// The `CMAKE_HOME_DIRECTORY` entry of a CMake cache: the source directory
+/// the build was configured from.
+fn cmake_source_dir(cache: &Path) -> Option<PathBuf> {
+    let text = fs::read_to_string(cache).ok()?;
+    text.lines()
+        .find_map(|line| line.strip_prefix("CMAKE_HOME_DIRECTORY:"))
+        .and_then(|entry| entry.split_once('='))
+        .map(|(_, path)| PathBuf::from(path.trim()))
+        .filter(|path| !path.as_os_str().is_empty())
+}
+
+/// Files that make a directory a project root, for grouping artifacts.
+const PROJECT_MARKERS: &[&str] = &[
+    "Cargo.toml", "package.json", "pyproject.toml", "setup.py", "requirements.txt", "build.gradle",
+    "build.gradle.kts", "settings.gradle", "settings.gradle.kts", "CMakeLists.txt", "go.mod", ".git",
+];
+
+#[derive(Clone, Debug)]
+pub struct Artifact {
+    pub path: PathBuf,
+    pub kind: ArtifactKind,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ArtifactProject {
+    pub root: PathBuf,
+    pub artifacts: Vec<Artifact>,
+    /// Newest modification of anything in the project outside its artifacts,
+    /// i.e. when someone last worked on it.
+    pub last_modified: Option<SystemTime>,
+}
+
+impl ArtifactProject {
+    pub fn size(&self) -> u64 {
+        self.artifacts.iter().map(|a| a.size).sum()
+    }
+}
+
+/// Finds artifact directories in the tree, grouped by the nearest enclosing
+/// project and sorted by reclaimable size, largest first.
+pub fn find_artifacts(root: &FileNode) -> Vec<ArtifactProject> {
+    let mut projects: BTreeMap<PathBuf, ArtifactProject> = BTreeMap::new();
+    let mut project_stack = Vec::new();
+    walk(root, &[], &mut project_stack, &mut projects);
+
+    let mut projects: Vec<ArtifactProject> = projects.into_values().collect();
+    for project in &mut projects {
+        if let Some(node) = root.find(&project.root) {
+            project.last_modified = newest_outside(node, &project.artifacts);
+        }
+        project.artifacts.sort_by_key(|a| std::cmp::Reverse(a.size));
+    }
+    projects.sort_by_key(|p| std::cmp::Reverse(p.size()));
+    projects
+}
+
+fn walk(node: &FileNode, siblings: &[FileNode], project_stack: &mut Vec<PathBuf>, projects: &mut BTreeMap<PathBuf, ArtifactProject>) {
+    if !node.is_dir || node.excluded {
+        return;
+    }
+    if let Some(kind) = ArtifactKind::detect(node, siblings) {
+        let parent = node.path.parent().map(PathBuf::from).unwrap_or_default();
+        let root = project_stack.last().cloned().unwrap_or(parent);
+        projects.entry(root.clone())
+            .or_insert_with(|| ArtifactProject { root, artifacts: Vec::new(), last_modified: None })
+            .artifacts
+            .push(Artifact { path: node.path.clone(), kind, size: node.size });
+        // Nested node_modules and the like belong to the artifact already found.
+        return;
+    }
+
+    let is_project = node.children.iter().any(|c| PROJECT_MARKERS.contains(&c.name.as_str()));
+    if is_project {
+        project_stack.push(node.path.clone());
+    }
+    for child in &node.children {
+        walk(child, &node.children, project_stack, projects);
+    }
+    if is_project {
+        project_stack.pop();
+    }
+}
+
+fn newest_outside(node: &FileNode, artifacts: &[Artifact]) -> Option<SystemTime> {
+    if artifacts.iter().any(|a| a.path == node.path) {
+        return None;
+    }
+    if !node.is_dir {
+        return node.modified;
+    }
+    // Only descend into directories that contain an artifact; elsewhere the
+    // precomputed newest time already covers the whole subtree.
+    if !artifacts.iter().any(|a| a.path.starts_with(&node.path)) {
+        return node.newest;
+    }
+    node.children.iter().filter_map(|c| newest_outside(c, artifacts)).max()
+}
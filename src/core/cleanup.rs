@@ -0,0 +1,170 @@
+use crate::core::scanner::FileNode;
+use glob::Pattern;
+use std::path::{Path, PathBuf};
+
+/// What a built-in rule points at.
+enum Target {
+    /// Everything inside a directory, except the named entries. The
+    /// directory itself stays: package managers expect it (and the lock
+    /// files and `partial/` folders they keep in it) to exist.
+    Contents { dir: &'static str, keep: &'static [&'static str] },
+    /// Files below a directory whose names match one of the globs.
+    Files { under: &'static str, names: &'static [&'static str] },
+    /// Entries of a directory not already covered by a more specific rule.
+    Remainder(&'static str),
+}
+
+struct CleanupRule {
+    label: &'static str,
+    description: &'static str,
+    target: Target,
+}
+
+/// Well-known reclaimable locations. Paths starting with `~` are relative to
+/// the home directory. `Remainder` rules must come after the rules they leave out.
+const RULES: &[CleanupRule] = &[
+    CleanupRule {
+        label: "apt package cache",
+        description: "Downloaded .deb files. apt fetches them again if a package is reinstalled.",
+        target: Target::Contents { dir: "/var/cache/apt/archives", keep: &["partial", "lock"] },
+    },
+    CleanupRule {
+        label: "pacman package cache",
+        description: "Old package versions. Removing them means you cannot downgrade without downloading.",
+        target: Target::Contents { dir: "/var/cache/pacman/pkg", keep: &[] },
+    },
+    CleanupRule {
+        label: "dnf cache",
+        description: "Repository metadata and packages. dnf rebuilds it on the next run.",
+        target: Target::Contents { dir: "/var/cache/dnf", keep: &[] },
+    },
+    CleanupRule {
+        label: "pip cache",
+        description: "Downloaded wheels. pip downloads them again when needed.",
+        target: Target::Contents { dir: "~/.cache/pip", keep: &[] },
+    },
+    CleanupRule {
+        label: "Cargo registry cache",
+        description: "Downloaded crate archives. Cargo fetches them again on the next build that needs them.",
+        target: Target::Contents { dir: "~/.cargo/registry/cache", keep: &[] },
+    },
+    CleanupRule {
+        label: "Cargo registry sources",
+        description: "Unpacked crate sources, extracted again from the registry cache.",
+        target: Target::Contents { dir: "~/.cargo/registry/src", keep: &[] },
+    },
+    CleanupRule {
+        label: "npm cache",
+        description: "npm's package cache. Installs get slower until it refills.",
+        target: Target::Contents { dir: "~/.npm/_cacache", keep: &[] },
+    },
+    CleanupRule {
+        label: "Yarn cache",
+        description: "Yarn's package cache. Installs get slower until it refills.",
+        target: Target::Contents { dir: "~/.cache/yarn", keep: &[] },
+    },
+    CleanupRule {
+        label: "Thumbnail cache",
+        description: "Image previews for file managers, regenerated when folders are browsed.",
+        target: Target::Contents { dir: "~/.cache/thumbnails", keep: &[] },
+    },
+    CleanupRule {
+        label: "Legacy thumbnail cache",
+        description: "Thumbnails from older desktops, no longer used by current file managers.",
+        target: Target::Contents { dir: "~/.thumbnails", keep: &[] },
+    },
+    CleanupRule {
+        label: "Archived journal files",
+        description: "Rotated systemd journal files. Older log history is lost; the active journal is kept.",
+        target: Target::Files { under: "/var/log/journal", names: &["*@*.journal", "*.journal~"] },
+    },
+    CleanupRule {
+        label: "Rotated logs",
+        description: "Compressed or numbered old log files. Only the history before the last rotation is lost.",
+        target: Target::Files { under: "/var/log", names: &["*.[0-9]", "*.[0-9].gz", "*.gz", "*.old"] },
+    },
+    CleanupRule {
+        label: "Crash reports",
+        description: "Crash reports from apport, only needed when filing a bug report.",
+        target: Target::Contents { dir: "/var/crash", keep: &[] },
+    },
+    CleanupRule {
+        label: "Core dumps",
+        description: "Memory dumps from crashed programs, only needed for debugging those crashes.",
+        target: Target::Contents { dir: "/var/lib/systemd/coredump", keep: &[] },
+    },
+    CleanupRule {
+        label: "Other user caches",
+        description: "Everything else in ~/.cache. Applications recreate it, but may start slower or re-download data.",
+        target: Target::Remainder("~/.cache"),
+    },
+];
+
+#[derive(Clone, Debug)]
+pub struct CleanupSuggestion {
+    pub label: &'static str,
+    pub description: &'static str,
+    /// Where the rule points, for display.
+    pub location: PathBuf,
+    /// What would be trashed.
+    pub paths: Vec<PathBuf>,
+    pub size: u64,
+}
+
+/// Evaluates the built-in rule pack against the scanned tree. Only
+/// locations inside the scan with a non-zero size are suggested.
+pub fn find_cleanup_suggestions(root: &FileNode) -> Vec<CleanupSuggestion> {
+    let mut suggestions: Vec<CleanupSuggestion> = Vec::new();
+    for rule in RULES {
+        let (location, nodes) = match &rule.target {
+            Target::Contents { dir, keep } => {
+                let location = expand(dir);
+                let nodes = root.find(&location)
+                    .map(|d| d.children.iter().filter(|c| !keep.contains(&c.name.as_str())).collect())
+                    .unwrap_or_default();
+                (location, nodes)
+            }
+            Target::Files { under, names } => {
+                let location = expand(under);
+                let patterns: Vec<Pattern> = names.iter().filter_map(|n| Pattern::new(n).ok()).collect();
+                let claimed: Vec<&Path> = suggestions.iter().flat_map(|s| &s.paths).map(|p| p.as_path()).collect();
+                let mut nodes = Vec::new();
+                if let Some(dir) = root.find(&location) {
+                    dir.visit_files(&mut |file| {
+                        if patterns.iter().any(|p| p.matches(&file.name)) && !claimed.contains(&file.path.as_path()) {
+                            nodes.push(file);
+                        }
+                    });
+                }
+                (location, nodes)
+            }
+            Target::Remainder(dir) => {
+                let location = expand(dir);
+                let claimed: Vec<&Path> = suggestions.iter().map(|s| s.location.as_path()).collect();
+                let nodes = root.find(&location)
+                    .map(|d| d.children.iter().filter(|c| !claimed.iter().any(|p| p.starts_with(&c.path))).collect())
+                    .unwrap_or_default();
+                (location, nodes)
+            }
+        };
+        let size: u64 = nodes.iter().map(|n| n.size).sum();
+        if size > 0 {
+            suggestions.push(CleanupSuggestion {
+                label: rule.label,
+                description: rule.description,
+                location,
+                paths: nodes.iter().map(|n| n.path.clone()).collect(),
+                size,
+            });
+        }
+    }
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.size));
+    suggestions
+}
+
+fn expand(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
@@ -1,22 +1,224 @@
-use sysinfo::Disks;
+use crossbeam_channel::{unbounded, Receiver};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use sysinfo::{DiskKind, Disks};
 
-#[derive(Clone, Debug)]
+/// How often free space is re-read while the disk list is on screen, and the
+/// mount-table poll interval where change notifications are unavailable.
+pub const DISK_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DiskInfo {
     pub name: String,
     pub mount_point: String,
     pub total_space: u64,
     pub available_space: u64,
+    pub file_system: String,
+    /// Device path, e.g. `/dev/nvme0n1p2`.
+    pub device: String,
+    #[serde(with = "disk_kind")]
+    pub kind: DiskKind,
+    pub is_removable: bool,
+    pub is_read_only: bool,
+    /// Inode totals from `statvfs`; `None` where the filesystem does not report them.
+    pub inodes_total: Option<u64>,
+    pub inodes_free: Option<u64>,
+    /// Further mount points of the same device (bind mounts, btrfs subvolumes),
+    /// filled in by [`storage_devices`].
+    pub also_mounted_at: Vec<String>,
+}
+
+impl DiskInfo {
+    pub fn kind_label(&self) -> &'static str {
+        match self.kind {
+            DiskKind::SSD => "SSD",
+            DiskKind::HDD => "HDD",
+            DiskKind::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// Memory-backed, kernel, container-layer and snap/loop mounts that are
+    /// not real storage a user would want to scan.
+    pub fn is_virtual(&self) -> bool {
+        VIRTUAL_FILESYSTEMS.contains(&self.file_system.as_str()) || self.device.starts_with("/dev/loop")
+    }
+
+    /// Fraction of the capacity in use.
+    pub fn usage(&self) -> f32 {
+        if self.total_space == 0 {
+            return 0.0;
+        }
+        1.0 - self.available_space as f32 / self.total_space as f32
+    }
+
+    /// Whichever runs out first, space or inodes.
+    pub fn fullness(&self) -> f32 {
+        self.usage().max(self.inode_usage().unwrap_or(0.0))
+    }
+
+    /// Fraction of inodes in use, if the filesystem has a fixed inode table.
+    pub fn inode_usage(&self) -> Option<f32> {
+        match (self.inodes_total, self.inodes_free) {
+            (Some(total), Some(free)) if total > 0 => Some(1.0 - free as f32 / total as f32),
+            _ => None,
+        }
+    }
+}
+
+/// `DiskKind` is a sysinfo type without serde support; it is sent to remote
+/// clients by label.
+mod disk_kind {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use sysinfo::DiskKind;
+
+    pub fn serialize<S: Serializer>(kind: &DiskKind, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match kind {
+            DiskKind::SSD => "SSD",
+            DiskKind::HDD => "HDD",
+            DiskKind::Unknown(_) => "Unknown",
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DiskKind, D::Error> {
+        Ok(match String::deserialize(deserializer)?.as_str() {
+            "SSD" => DiskKind::SSD,
+            "HDD" => DiskKind::HDD,
+            _ => DiskKind::Unknown(-1),
+        })
+    }
 }
 
+const VIRTUAL_FILESYSTEMS: &[&str] = &[
+    "tmpfs", "devtmpfs", "ramfs", "overlay", "squashfs", "proc", "sysfs", "devpts", "cgroup", "cgroup2",
+    "securityfs", "debugfs", "tracefs", "configfs", "fusectl", "pstore", "bpf", "mqueue", "hugetlbfs",
+    "autofs", "efivarfs", "binfmt_misc", "nsfs", "rpc_pipefs", "fuse.snapfuse", "fuse.portal", "fuse.gvfsd-fuse",
+];
+
 pub fn get_disks() -> Vec<DiskInfo> {
     let disks = Disks::new_with_refreshed_list();
     
     disks.iter()
-        .map(|disk| DiskInfo {
-            name: disk.name().to_string_lossy().to_string(),
-            mount_point: disk.mount_point().to_string_lossy().to_string(),
-            total_space: disk.total_space(),
-            available_space: disk.available_space(),
+        .map(|disk| {
+            let stats = fs_stats(disk.mount_point());
+            DiskInfo {
+                name: disk.name().to_string_lossy().to_string(),
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                total_space: disk.total_space(),
+                available_space: disk.available_space(),
+                file_system: disk.file_system().to_string_lossy().to_string(),
+                device: disk.name().to_string_lossy().to_string(),
+                kind: disk.kind(),
+                is_removable: disk.is_removable(),
+                is_read_only: stats.as_ref().is_some_and(|s| s.read_only),
+                inodes_total: stats.as_ref().and_then(|s| s.inodes_total),
+                inodes_free: stats.as_ref().and_then(|s| s.inodes_free),
+                also_mounted_at: Vec::new(),
+            }
         })
         .collect()
 }
+
+struct FsStats {
+    read_only: bool,
+    inodes_total: Option<u64>,
+    inodes_free: Option<u64>,
+}
+
+#[cfg(unix)]
+fn fs_stats(mount_point: &Path) -> Option<FsStats> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(mount_point.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a properly sized out-parameter.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // Filesystems without a fixed inode table (btrfs, ZFS, most network mounts) report zero.
+    let inodes_total = (stat.f_files > 0).then_some(stat.f_files as u64);
+    Some(FsStats {
+        read_only: stat.f_flag & libc::ST_RDONLY != 0,
+        inodes_total,
+        inodes_free: inodes_total.map(|_| stat.f_favail as u64),
+    })
+}
+
+#[cfg(not(unix))]
+fn fs_stats(_mount_point: &Path) -> Option<FsStats> {
+    None
+}
+
+/// What the disk picker shows by default: virtual filesystems dropped and
+/// mounts of the same device collapsed into the one with the shortest mount
+/// point, the others listed in `also_mounted_at`. Network shares keep their
+/// own entry since their "device" is a remote path rather than a block device.
+pub fn storage_devices(disks: &[DiskInfo]) -> Vec<DiskInfo> {
+    let mut sorted: Vec<&DiskInfo> = disks.iter().filter(|d| !d.is_virtual()).collect();
+    sorted.sort_by_key(|d| d.mount_point.len());
+
+    let mut devices: Vec<DiskInfo> = Vec::new();
+    for disk in sorted {
+        let primary = devices.iter_mut()
+            .find(|d| d.device.starts_with("/dev/") && d.device == disk.device);
+        match primary {
+            Some(primary) => primary.also_mounted_at.push(disk.mount_point.clone()),
+            None => devices.push(disk.clone()),
+        }
+    }
+    // Restore the system's mount order for a stable list.
+    devices.sort_by_key(|d| disks.iter().position(|o| o.mount_point == d.mount_point));
+    devices
+}
+
+/// Finds the disk a path lives on by picking the longest matching mount point.
+pub fn disk_for_path<'a>(disks: &'a [DiskInfo], path: &Path) -> Option<&'a DiskInfo> {
+    disks.iter()
+        .filter(|d| path.starts_with(&d.mount_point))
+        .max_by_key(|d| d.mount_point.len())
+}
+
+/// Reports mount and unmount events so the disk list can follow hot-plugged
+/// drives and newly mounted shares. On Linux this waits on
+/// `/proc/self/mountinfo`, which the kernel flags whenever the mount table
+/// changes; elsewhere it falls back to a timer.
+pub struct MountWatcher {
+    rx: Receiver<()>,
+}
+
+impl MountWatcher {
+    pub fn new() -> Self {
+        let (tx, rx) = unbounded();
+        thread::spawn(move || {
+            #[cfg(target_os = "linux")]
+            if let Ok(file) = std::fs::File::open("/proc/self/mountinfo") {
+                use std::os::unix::io::AsRawFd;
+                let mut fds = libc::pollfd { fd: file.as_raw_fd(), events: libc::POLLPRI, revents: 0 };
+                loop {
+                    // SAFETY: `fds` points at one valid pollfd whose fd stays open for the loop.
+                    let ready = unsafe { libc::poll(&mut fds, 1, -1) };
+                    if ready < 0 {
+                        break;
+                    }
+                    if fds.revents & (libc::POLLPRI | libc::POLLERR) != 0 && tx.send(()).is_err() {
+                        return;
+                    }
+                }
+            }
+            loop {
+                thread::sleep(DISK_REFRESH_INTERVAL);
+                if tx.send(()).is_err() {
+                    return;
+                }
+            }
+        });
+        Self { rx }
+    }
+
+    /// True if the mount table changed since the last call.
+    pub fn changed(&self) -> bool {
+        self.rx.try_iter().count() > 0
+    }
+}
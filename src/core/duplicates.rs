@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use xxhash_rust::xxh3::Xxh3;
+use crate::core::scanner::FileNode;
+
+/// Bytes read from the start and the end of a file for the partial hash.
+const PARTIAL_CHUNK: u64 = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateStage {
+    PartialHash,
+    FullHash,
+}
+
+impl DuplicateStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DuplicateStage::PartialHash => "Comparing file headers",
+            DuplicateStage::FullHash => "Hashing full contents",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DuplicateProgress {
+    pub stage: DuplicateStage,
+    pub files_done: u64,
+    pub files_total: u64,
+    pub bytes_hashed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Bytes that would be freed by keeping only one copy.
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64).saturating_sub(1)
+    }
+}
+
+pub enum DuplicateMessage {
+    Progress(DuplicateProgress),
+    Completed(Vec<DuplicateGroup>),
+    Cancelled,
+}
+
+/// Finds groups of identical files in a scanned tree on a pool of worker threads:
+/// files are grouped by size, then by a partial hash, then by a full hash.
+pub struct DuplicateFinder {
+    rx: Receiver<DuplicateMessage>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl DuplicateFinder {
+    pub fn new(root: &FileNode, min_size: u64) -> Self {
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        root.visit_files(&mut |f| {
+            if f.size >= min_size.max(1) {
+                by_size.entry(f.size).or_default().push(f.path.clone());
+            }
+        });
+        let candidates: Vec<DuplicateGroup> = by_size.into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(size, paths)| DuplicateGroup { size, paths })
+            .collect();
+
+        let (tx, rx) = unbounded();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancel_clone = cancel.clone();
+        thread::spawn(move || {
+            let msg = match find_duplicates(candidates, &tx, &cancel_clone) {
+                Some(mut groups) => {
+                    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted()));
+                    DuplicateMessage::Completed(groups)
+                }
+                None => DuplicateMessage::Cancelled,
+            };
+            let _ = tx.send(msg);
+        });
+        Self { rx, cancel }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn try_recv(&self) -> Option<DuplicateMessage> {
+        self.rx.try_recv().ok()
+    }
+}
+
+fn find_duplicates(
+    candidates: Vec<DuplicateGroup>,
+    tx: &Sender<DuplicateMessage>,
+    cancel: &AtomicBool,
+) -> Option<Vec<DuplicateGroup>> {
+    let candidates: Vec<DuplicateGroup> = candidates.into_iter().map(drop_hard_links).filter(|g| g.paths.len() > 1).collect();
+    let partial = refine(candidates, DuplicateStage::PartialHash, tx, cancel)?;
+    refine(partial, DuplicateStage::FullHash, tx, cancel)
+}
+
+/// Splits every group by the hash for `stage`, dropping groups left with a single file.
+fn refine(
+    groups: Vec<DuplicateGroup>,
+    stage: DuplicateStage,
+    tx: &Sender<DuplicateMessage>,
+    cancel: &AtomicBool,
+) -> Option<Vec<DuplicateGroup>> {
+    let jobs: Vec<(u64, PathBuf)> = groups.iter()
+        .flat_map(|g| g.paths.iter().map(move |p| (g.size, p.clone())))
+        .collect();
+    let files_total = jobs.len() as u64;
+    let files_done = AtomicU64::new(0);
+    let bytes_hashed = AtomicU64::new(0);
+
+    let (job_tx, job_rx) = unbounded::<(u64, PathBuf)>();
+    for job in jobs {
+        let _ = job_tx.send(job);
+    }
+    drop(job_tx);
+
+    let (hash_tx, hash_rx) = unbounded::<(u64, u128, PathBuf)>();
+    let workers = thread::available_parallelism().map_or(4, |n| n.get()).min(8);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let job_rx = job_rx.clone();
+            let hash_tx = hash_tx.clone();
+            let (files_done, bytes_hashed) = (&files_done, &bytes_hashed);
+            scope.spawn(move || {
+                for (size, path) in job_rx.iter() {
+                    if cancel.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let hashed = match stage {
+                        DuplicateStage::PartialHash => partial_hash(&path, size),
+                        DuplicateStage::FullHash => full_hash(&path, cancel),
+                    };
+                    let done = files_done.fetch_add(1, Ordering::Relaxed) + 1;
+                    if let Ok((hash, read)) = hashed {
+                        bytes_hashed.fetch_add(read, Ordering::Relaxed);
+                        let _ = hash_tx.send((size, hash, path));
+                    }
+                    let _ = tx.send(DuplicateMessage::Progress(DuplicateProgress {
+                        stage,
+                        files_done: done,
+                        files_total,
+                        bytes_hashed: bytes_hashed.load(Ordering::Relaxed),
+                    }));
+                }
+            });
+        }
+    });
+    drop(hash_tx);
+
+    if cancel.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let mut by_hash: HashMap<(u64, u128), Vec<PathBuf>> = HashMap::new();
+    for (size, hash, path) in hash_rx.iter() {
+        by_hash.entry((size, hash)).or_default().push(path);
+    }
+    Some(by_hash.into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|((size, _), mut paths)| {
+            paths.sort();
+            DuplicateGroup { size, paths }
+        })
+        .collect())
+}
+
+fn partial_hash(path: &Path, size: u64) -> Result<(u128, u64)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buf = Vec::with_capacity(PARTIAL_CHUNK as usize);
+    let mut read = 0;
+
+    // A single read may return less than asked for; read the chunk in full
+    // so identical files always hash the same.
+    read += (&mut file).take(PARTIAL_CHUNK).read_to_end(&mut buf)? as u64;
+    hasher.update(&buf);
+    if size > PARTIAL_CHUNK * 2 {
+        file.seek(SeekFrom::End(-(PARTIAL_CHUNK as i64)))?;
+        buf.clear();
+        read += (&mut file).take(PARTIAL_CHUNK).read_to_end(&mut buf)? as u64;
+        hasher.update(&buf);
+    }
+    Ok((hasher.digest128(), read))
+}
+
+fn full_hash(path: &Path, cancel: &AtomicBool) -> Result<(u128, u64)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0u8; 1 << 20];
+    let mut read = 0;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            bail!("cancelled");
+        }
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        read += n as u64;
+    }
+    Ok((hasher.digest128(), read))
+}
+
+/// Paths that already share an inode are not wasting space; keep one of each.
+#[cfg(unix)]
+fn drop_hard_links(mut group: DuplicateGroup) -> DuplicateGroup {
+    use std::os::unix::fs::MetadataExt;
+    let mut seen = std::collections::HashSet::new();
+    group.paths.retain(|p| match fs::metadata(p) {
+        Ok(m) => seen.insert((m.dev(), m.ino())),
+        Err(_) => false,
+    });
+    group
+}
+
+#[cfg(not(unix))]
+fn drop_hard_links(group: DuplicateGroup) -> DuplicateGroup {
+    group
+}
+
+/// Replaces `duplicate` with a hard link to `keep`. The link is created under a
+/// temporary name first and renamed over the duplicate so it is never missing.
+/// Both files are hashed again first, as either may have changed since the
+/// search and the duplicate's new content would be lost.
+pub fn replace_with_hard_link(keep: &Path, duplicate: &Path) -> Result<()> {
+    let not_cancelled = AtomicBool::new(false);
+    let hash = |path: &Path| full_hash(path, &not_cancelled).with_context(|| format!("Failed to read {}", path.display()));
+    if hash(keep)? != hash(duplicate)? {
+        bail!("{} and {} are no longer identical", keep.display(), duplicate.display());
+    }
+    let parent = duplicate.parent().context("Duplicate has no parent directory")?;
+    let mut tmp_name = duplicate.file_name().context("Duplicate has no file name")?.to_os_string();
+    tmp_name.push(".gate-link");
+    let tmp = parent.join(tmp_name);
+
+    fs::hard_link(keep, &tmp)
+        .with_context(|| format!("Failed to link {} to {}", keep.display(), duplicate.display()))?;
+    if let Err(e) = fs::rename(&tmp, duplicate) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("Failed to replace {}", duplicate.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_links_only_identical_files() {
+        let dir = std::env::temp_dir().join(format!("gate-duplicates-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (keep, same, changed) = (dir.join("keep"), dir.join("same"), dir.join("changed"));
+        fs::write(&keep, "contents").unwrap();
+        fs::write(&same, "contents").unwrap();
+        // Changed after the search, keeping the same size.
+        fs::write(&changed, "CONTENTS").unwrap();
+
+        let linked = replace_with_hard_link(&keep, &same);
+        let refused = replace_with_hard_link(&keep, &changed);
+        let changed_contents = fs::read_to_string(&changed);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(linked.is_ok());
+        assert!(refused.is_err());
+        assert_eq!(changed_contents.unwrap(), "CONTENTS");
+    }
+}
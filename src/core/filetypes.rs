@@ -0,0 +1,141 @@
+use crate::core::scanner::FileNode;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileCategory {
+    Video,
+    Images,
+    Audio,
+    Archives,
+    SourceCode,
+    Documents,
+    Binaries,
+    VmDisks,
+    Other,
+}
+
+impl FileCategory {
+    pub const ALL: [FileCategory; 9] = [
+        FileCategory::Video,
+        FileCategory::Images,
+        FileCategory::Audio,
+        FileCategory::Archives,
+        FileCategory::SourceCode,
+        FileCategory::Documents,
+        FileCategory::Binaries,
+        FileCategory::VmDisks,
+        FileCategory::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileCategory::Video => "Video",
+            FileCategory::Images => "Images",
+            FileCategory::Audio => "Audio",
+            FileCategory::Archives => "Archives",
+            FileCategory::SourceCode => "Source code",
+            FileCategory::Documents => "Documents",
+            FileCategory::Binaries => "Binaries",
+            FileCategory::VmDisks => "VM disks",
+            FileCategory::Other => "Other",
+        }
+    }
+
+    pub fn from_extension(ext: &str) -> Self {
+        match ext {
+            "mp4" | "mkv" | "avi" | "mov" | "wmv" | "flv" | "webm" | "m4v" | "mpg" | "mpeg" | "m2ts" | "vob" => FileCategory::Video,
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "svg" | "heic" | "raw" | "cr2" | "nef"
+            | "dng" | "ico" | "psd" | "xcf" => FileCategory::Images,
+            "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "opus" | "wma" | "aiff" => FileCategory::Audio,
+            "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "7z" | "rar" | "iso" | "dmg" | "deb" | "rpm"
+            | "apk" | "cab" | "lz4" => FileCategory::Archives,
+            "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "py" | "js" | "ts" | "jsx" | "tsx" | "go" | "java" | "kt"
+            | "swift" | "rb" | "php" | "cs" | "sh" | "html" | "css" | "scss" | "json" | "toml" | "yaml" | "yml"
+            | "xml" | "md" | "sql" | "lua" | "vue" => FileCategory::SourceCode,
+            "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "txt" | "csv"
+            | "epub" | "rtf" => FileCategory::Documents,
+            "exe" | "dll" | "so" | "dylib" | "a" | "o" | "lib" | "bin" | "elf" | "class" | "jar" | "wasm"
+            | "pyc" | "msi" | "rlib" | "node" => FileCategory::Binaries,
+            "vmdk" | "vdi" | "qcow" | "qcow2" | "vhd" | "vhdx" | "img" | "ova" | "hdd" => FileCategory::VmDisks,
+            _ => FileCategory::Other,
+        }
+    }
+
+    pub fn of(node: &FileNode) -> Self {
+        node.extension().map_or(FileCategory::Other, |e| Self::from_extension(&e))
+    }
+}
+
+/// What the tree is narrowed to after clicking a row in the type breakdown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeFilter {
+    Extension(String),
+    Category(FileCategory),
+}
+
+impl TypeFilter {
+    pub fn matches(&self, node: &FileNode) -> bool {
+        if node.is_dir {
+            return false;
+        }
+        match self {
+            TypeFilter::Extension(ext) => node.extension().unwrap_or_default() == *ext,
+            TypeFilter::Category(cat) => FileCategory::of(node) == *cat,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            TypeFilter::Extension(ext) if ext.is_empty() => "(no extension)".to_string(),
+            TypeFilter::Extension(ext) => format!(".{}", ext),
+            TypeFilter::Category(cat) => cat.label().to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeStat {
+    pub filter: TypeFilter,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// Per-extension and per-category totals, each sorted by bytes descending.
+#[derive(Clone, Debug, Default)]
+pub struct TypeBreakdown {
+    pub total_bytes: u64,
+    pub total_files: u64,
+    pub by_extension: Vec<TypeStat>,
+    pub by_category: Vec<TypeStat>,
+}
+
+impl TypeBreakdown {
+    pub fn compute(root: &FileNode) -> Self {
+        let mut extensions: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut categories: HashMap<FileCategory, (u64, u64)> = HashMap::new();
+        let mut breakdown = TypeBreakdown::default();
+
+        root.visit_files(&mut |file| {
+            let ext = file.extension().unwrap_or_default();
+            let category = FileCategory::from_extension(&ext);
+            for entry in [extensions.entry(ext).or_default(), categories.entry(category).or_default()] {
+                entry.0 += file.size;
+                entry.1 += 1;
+            }
+            breakdown.total_bytes += file.size;
+            breakdown.total_files += 1;
+        });
+
+        breakdown.by_extension = collect(extensions, TypeFilter::Extension);
+        breakdown.by_category = collect(categories, TypeFilter::Category);
+        breakdown
+    }
+}
+
+fn collect<K>(map: HashMap<K, (u64, u64)>, to_filter: fn(K) -> TypeFilter) -> Vec<TypeStat> {
+    let mut stats: Vec<TypeStat> = map.into_iter()
+        .map(|(k, (bytes, files))| TypeStat { filter: to_filter(k), bytes, files })
+        .collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.bytes));
+    stats
+}
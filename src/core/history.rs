@@ -0,0 +1,120 @@
+use crate::core::disk::DiskInfo;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// At most one sample per mount is taken in this interval, so a long session
+/// keeps one point per hour and the file is written at most hourly, not on
+/// every refresh of the disk list.
+const SAMPLE_INTERVAL_SECS: i64 = 60 * 60;
+/// Roughly a year of hourly samples per mount.
+const MAX_SAMPLES: usize = 24 * 366;
+/// The trend is fitted over recent samples only, so an old cleanup does not
+/// hide a disk that is filling up now.
+const TREND_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+const SECS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageSample {
+    /// Unix timestamp in seconds.
+    pub time: i64,
+    pub used: u64,
+    pub total: u64,
+}
+
+/// Used space per mount point over time, stored as TOML under the XDG data
+/// directory (`~/.local/share/gate/usage_history.toml`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageHistory {
+    pub mounts: BTreeMap<String, Vec<UsageSample>>,
+}
+
+impl UsageHistory {
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("gate").join("usage_history.toml"))
+    }
+
+    /// Loads the recorded samples, empty only if there is no file yet. A file
+    /// that cannot be read or parsed is an error, so it is not saved over.
+    pub fn try_load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        toml::from_str(&text).with_context(|| format!("Invalid usage history file {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("No data directory available")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let text = toml::to_string(self)?;
+        fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Adds a sample for each disk whose last one is at least an hour old.
+    /// Returns true if anything was added and the history should be saved.
+    pub fn record(&mut self, disks: &[DiskInfo], now: i64) -> bool {
+        let mut changed = false;
+        for disk in disks.iter().filter(|d| d.total_space > 0) {
+            let samples = self.mounts.entry(disk.mount_point.clone()).or_default();
+            if samples.last().is_some_and(|last| now - last.time < SAMPLE_INTERVAL_SECS) {
+                continue;
+            }
+            samples.push(UsageSample {
+                time: now,
+                used: disk.total_space - disk.available_space.min(disk.total_space),
+                total: disk.total_space,
+            });
+            if samples.len() > MAX_SAMPLES {
+                samples.drain(..samples.len() - MAX_SAMPLES);
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn samples(&self, mount_point: &str) -> &[UsageSample] {
+        self.mounts.get(mount_point).map_or(&[], |s| s.as_slice())
+    }
+
+    /// Growth in bytes per day over the trend window, by least squares.
+    /// `None` until the samples span at least a day.
+    pub fn growth_per_day(&self, mount_point: &str) -> Option<f64> {
+        let samples = self.samples(mount_point);
+        let latest = samples.last()?.time;
+        let recent: Vec<&UsageSample> = samples.iter().filter(|s| latest - s.time <= TREND_WINDOW_SECS).collect();
+        let first = recent.first()?.time;
+        if (latest - first) as f64 <= SECS_PER_DAY {
+            return None;
+        }
+
+        let n = recent.len() as f64;
+        let xs: Vec<f64> = recent.iter().map(|s| (s.time - first) as f64 / SECS_PER_DAY).collect();
+        let mean_x = xs.iter().sum::<f64>() / n;
+        let mean_y = recent.iter().map(|s| s.used as f64).sum::<f64>() / n;
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (x, s) in xs.iter().zip(&recent) {
+            covariance += (x - mean_x) * (s.used as f64 - mean_y);
+            variance += (x - mean_x) * (x - mean_x);
+        }
+        (variance > 0.0).then(|| covariance / variance)
+    }
+
+    /// Projected days until the mount is full at its current growth rate.
+    /// `None` if there is not enough history or usage is flat or shrinking.
+    pub fn days_until_full(&self, mount_point: &str) -> Option<f64> {
+        let growth = self.growth_per_day(mount_point)?;
+        let last = self.samples(mount_point).last()?;
+        (growth > 0.0).then(|| last.total.saturating_sub(last.used) as f64 / growth)
+    }
+}
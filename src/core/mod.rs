@@ -1,2 +1,16 @@
+pub mod age;
+pub mod alerts;
+pub mod artifacts;
+pub mod cleanup;
 pub mod disk;
+pub mod duplicates;
+pub mod filetypes;
+pub mod history;
+pub mod ncdu;
+pub mod remote;
+pub mod report;
+pub mod rules;
 pub mod scanner;
+pub mod search;
+pub mod settings;
+pub mod transfer;
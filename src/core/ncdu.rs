@@ -0,0 +1,267 @@
+use crate::core::scanner::FileNode;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Major version of the ncdu export format (`ncdu -o`); minor versions only
+/// add optional fields.
+const MAJOR_VERSION: u64 = 1;
+const MINOR_VERSION: u64 = 2;
+
+/// Reads an `ncdu -o` JSON export into a tree. Paths are rebuilt from the
+/// root name, so they refer to the machine the export was made on.
+pub fn import(path: &Path) -> Result<FileNode> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut tokens = Tokens { text: &text, pos: 0 };
+    let mut next = || tokens.next().transpose().with_context(|| format!("{} is not valid JSON", path.display()));
+    if !matches!(next()?, Some(Token::Open)) {
+        bail!("{} is not an ncdu export", path.display());
+    }
+    // The major and minor version, then metadata.
+    let header = [next()?, next()?, next()?];
+    let [Some(Token::Value(major)), Some(Token::Value(_)), Some(Token::Value(_))] = header else {
+        bail!("{} is not an ncdu export of format version {}", path.display(), MAJOR_VERSION);
+    };
+    if major.as_u64() != Some(MAJOR_VERSION) {
+        bail!("{} is not an ncdu export of format version {}", path.display(), MAJOR_VERSION);
+    }
+    let root = read_tree(&mut tokens).with_context(|| format!("Invalid ncdu export {}", path.display()))?;
+    if !root.is_dir {
+        bail!("{} does not contain a directory", path.display());
+    }
+    Ok(root)
+}
+
+/// Writes `root` in ncdu's export format, which `ncdu -f` can browse.
+pub fn export(root: &FileNode, path: &Path) -> Result<()> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let meta = json!({
+        "progname": "gate",
+        "progver": env!("CARGO_PKG_VERSION"),
+        "timestamp": timestamp,
+    });
+    // ncdu names the root by its full path; a combined multi-location root
+    // has none, so its children carry theirs instead.
+    let root_entry = if root.path.as_os_str().is_empty() {
+        let mut items = vec![json!({ "name": root.name })];
+        items.extend(root.children.iter().map(|c| write_entry(c, c.path.display().to_string())));
+        Value::Array(items)
+    } else {
+        write_entry(root, root.path.display().to_string())
+    };
+    let document = json!([MAJOR_VERSION, MINOR_VERSION, meta, root_entry]);
+    let text = serde_json::to_string(&document)?;
+    fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+enum Token {
+    Open,
+    Close,
+    Value(Value),
+}
+
+/// Splits JSON into array brackets and the values between them. Arrays nest
+/// as deep as the exported directories, past what serde_json parses in one
+/// go, so only the flat info objects and header values are left to it.
+struct Tokens<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Token>;
+
+    fn next(&mut self) -> Option<Result<Token>> {
+        let rest = &self.text[self.pos..];
+        let value = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        self.pos += rest.len() - value.len();
+        match value.as_bytes().first()? {
+            b'[' => {
+                self.pos += 1;
+                Some(Ok(Token::Open))
+            }
+            b']' => {
+                self.pos += 1;
+                Some(Ok(Token::Close))
+            }
+            _ => {
+                let mut values = serde_json::Deserializer::from_str(value).into_iter::<Value>();
+                let value = values.next()?;
+                self.pos += values.byte_offset();
+                Some(value.map(Token::Value).map_err(Into::into))
+            }
+        }
+    }
+}
+
+/// Reads one entry. A directory is `[info, children...]`, anything else a
+/// bare info object; open directories are kept on a stack rather than
+/// recursed into.
+fn read_tree(tokens: &mut Tokens) -> Result<FileNode> {
+    let mut open: Vec<FileNode> = Vec::new();
+    loop {
+        let parent = open.last().map(|dir| dir.path.as_path());
+        let node = match tokens.next().context("Unexpected end of file")?? {
+            Token::Open => {
+                let Some(Token::Value(info)) = tokens.next().transpose()? else {
+                    bail!("Directory without an info object");
+                };
+                let dir = read_info(info, parent, true)?;
+                open.push(dir);
+                continue;
+            }
+            Token::Value(info) => read_info(info, parent, false)?,
+            Token::Close => open.pop().context("Unexpected end of a directory")?,
+        };
+        match open.last_mut() {
+            Some(dir) => {
+                dir.add_child_totals(&node);
+                dir.children.push(node);
+            }
+            None => return Ok(node),
+        }
+    }
+}
+
+fn read_info(info: Value, parent: Option<&Path>, is_dir: bool) -> Result<FileNode> {
+    let Value::Object(info) = info else {
+        bail!("Unexpected entry {}", info);
+    };
+    let name = info.get("name").and_then(Value::as_str).context("Entry without a name")?.to_string();
+    let path = match parent {
+        Some(parent) => parent.join(&name),
+        None => PathBuf::from(&name),
+    };
+    let number = |key: &str| info.get(key).and_then(Value::as_u64);
+    let flag = |key: &str| info.get(key).and_then(Value::as_bool).unwrap_or(false);
+    let modified = number("mtime").map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+
+    let mut node = FileNode {
+        name: path.file_name().map_or(name, |n| n.to_string_lossy().to_string()),
+        is_dir,
+        files: u64::from(!is_dir),
+        modified,
+        read_error: flag("read_error"),
+        excluded: info.get("excluded").is_some(),
+        owner: number("uid").and_then(|uid| u32::try_from(uid).ok()),
+        ..Default::default()
+    };
+    if !is_dir && !node.excluded {
+        node.size = number("asize").unwrap_or(0);
+        node.disk_usage = number("dsize").unwrap_or(0);
+        node.hard_link = if flag("hlnkc") { number("ino") } else { None };
+        node.newest = modified;
+        node.oldest = modified;
+    }
+    node.path = path;
+    Ok(node)
+}
+
+fn write_entry(node: &FileNode, name: String) -> Value {
+    let mut info = Map::new();
+    info.insert("name".to_string(), Value::String(name));
+    if !node.is_dir && !node.excluded {
+        info.insert("asize".to_string(), json!(node.size));
+        info.insert("dsize".to_string(), json!(node.disk_usage));
+    }
+    if let Some(ino) = node.hard_link {
+        info.insert("ino".to_string(), json!(ino));
+        info.insert("hlnkc".to_string(), Value::Bool(true));
+    }
+    if node.read_error {
+        info.insert("read_error".to_string(), Value::Bool(true));
+    }
+    if node.excluded {
+        info.insert("excluded".to_string(), Value::String("pattern".to_string()));
+    }
+    if let Some(uid) = node.owner {
+        info.insert("uid".to_string(), json!(uid));
+    }
+    if let Some(secs) = node.modified.and_then(|m| m.duration_since(UNIX_EPOCH).ok()) {
+        info.insert("mtime".to_string(), json!(secs.as_secs()));
+    }
+
+    // ncdu has no placeholder for an excluded directory's contents.
+    if node.is_dir && !node.excluded {
+        let mut items = vec![Value::Object(info)];
+        items.extend(node.children.iter().map(|c| write_entry(c, c.name.clone())));
+        Value::Array(items)
+    } else {
+        Value::Object(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scanner::{MultiScanner, ScanMessage, ScanOptions};
+
+    #[test]
+    fn deep_exports_round_trip() {
+        let mut node = FileNode { name: "file".to_string(), size: 10, files: 1, ..Default::default() };
+        for level in (0..300).rev() {
+            let mut dir = FileNode { name: level.to_string(), is_dir: true, ..Default::default() };
+            dir.add_child_totals(&node);
+            dir.children.push(node);
+            node = dir;
+        }
+        node.name = "/data".to_string();
+        node.path = PathBuf::from("/data");
+
+        let file = std::env::temp_dir().join(format!("gate-ncdu-{}.json", std::process::id()));
+        export(&node, &file).unwrap();
+        let imported = import(&file);
+        let _ = fs::remove_file(&file);
+        let imported = imported.unwrap();
+
+        assert_eq!((imported.size, imported.files, imported.dirs), (node.size, node.files, node.dirs));
+        let mut deepest = &imported;
+        while let Some(child) = deepest.children.first() {
+            deepest = child;
+        }
+        let expected: PathBuf = ["/data"].into_iter().map(String::from).chain((1..300).map(|l| l.to_string())).chain(["file".to_string()]).collect();
+        assert_eq!(deepest.path, expected);
+        assert_eq!(deepest.size, 10);
+    }
+
+    #[test]
+    fn excluded_entries_count_as_in_a_scan() {
+        let dir = std::env::temp_dir().join(format!("gate-ncdu-excluded-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("kept")).unwrap();
+        fs::create_dir_all(dir.join("skipped-dir/inner")).unwrap();
+        fs::write(dir.join("kept/file"), "x").unwrap();
+        fs::write(dir.join("skipped.log"), "x").unwrap();
+        let options = ScanOptions { exclusions: vec!["skipped*".to_string()], ..Default::default() };
+        let mut scanner = MultiScanner::new(vec![dir.clone()], options);
+        let scanned = loop {
+            match scanner.try_recv() {
+                Some(ScanMessage::Completed(root)) => break root,
+                Some(ScanMessage::Error(e)) => panic!("{}", e),
+                _ => std::thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        let file = dir.with_extension("json");
+        export(&scanned, &file).unwrap();
+        let imported = import(&file);
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_file(&file);
+        let imported = imported.unwrap();
+
+        assert_eq!((scanned.files, scanned.dirs), (1, 1));
+        assert_eq!((imported.files, imported.dirs, imported.size), (scanned.files, scanned.dirs, scanned.size));
+        assert_eq!(imported.children.iter().filter(|c| c.excluded).count(), 2);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let file = std::env::temp_dir().join(format!("gate-ncdu-other-{}.json", std::process::id()));
+        for text in ["{}", "[2, 0, {}, [{\"name\": \"/\"}]]", "[1, 2, {}, [{\"name\": \"/\"}, [{}]]]", "[1, 2, {}, [{\"name\": \"/\"}"] {
+            fs::write(&file, text).unwrap();
+            assert!(import(&file).is_err(), "{}", text);
+        }
+        let _ = fs::remove_file(&file);
+    }
+}
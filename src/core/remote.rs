@@ -0,0 +1,571 @@
+use crate::core::disk::{get_disks, DiskInfo};
+use crate::core::scanner::{FileNode, MultiScanner, ScanMessage, ScanOptions, ScanProgress};
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever requests or responses change incompatibly.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Minimum time between progress messages sent by the agent.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How the GUI reaches a `gate agent` process.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transport {
+    /// Runs `COMMAND agent` on `host` through the system `ssh`, so keys,
+    /// jump hosts and aliases from `~/.ssh/config` apply.
+    Ssh { host: String, command: String },
+    /// Serves requests from a thread over in-process pipes, which exercises
+    /// the whole protocol without an SSH server.
+    Loopback,
+}
+
+impl Transport {
+    pub fn label(&self) -> String {
+        match self {
+            Transport::Ssh { host, .. } => host.clone(),
+            Transport::Loopback => "loopback".to_string(),
+        }
+    }
+}
+
+/// One JSON object per line from client to agent.
+#[derive(Serialize, Deserialize)]
+enum Request {
+    Disks,
+    Scan { paths: Vec<PathBuf>, options: ScanOptions },
+    Delete { paths: Vec<PathBuf> },
+}
+
+/// One JSON object per line from agent to client, answering requests in order.
+#[derive(Serialize, Deserialize)]
+enum Response {
+    Hello { version: u32 },
+    Disks(Vec<DiskInfo>),
+    Progress { files: u64, bytes: u64, path: String },
+    /// One node of a finished scan. Nodes come parent first, each on its own
+    /// line, as nesting a whole disk in one message would exceed the JSON
+    /// parser's depth limit.
+    Node(WireNode),
+    /// Ends a scan result of `nodes` nodes.
+    Tree { nodes: usize },
+    Deleted(Vec<PathBuf>),
+    Error(String),
+}
+
+/// Compact form of a `FileNode`. Paths are rebuilt from names and directory
+/// totals from their contents, so neither is sent; short keys and skipped
+/// defaults keep whole-disk trees small.
+#[derive(Serialize, Deserialize)]
+struct WireNode {
+    /// Index of the parent among the nodes sent before; none for the root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    i: Option<usize>,
+    n: String,
+    /// Only where the path is not the parent's joined with the name, i.e. roots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    p: Option<String>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    s: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    u: u64,
+    #[serde(default, skip_serializing_if = "is_false")]
+    d: bool,
+    /// Modified, accessed and changed, in seconds since the epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    m: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    a: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    b: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    h: Option<u64>,
+    /// Owner user id; names are looked up where the tree is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    o: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    e: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    x: bool,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+fn to_secs(time: Option<SystemTime>) -> Option<u64> {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs())
+}
+
+fn from_secs(secs: Option<u64>) -> Option<SystemTime> {
+    secs.map(|s| UNIX_EPOCH + Duration::from_secs(s))
+}
+
+fn to_wire(node: &FileNode, parent: Option<(usize, &Path)>) -> WireNode {
+    let parent_path = parent.map_or(Path::new(""), |(_, path)| path);
+    WireNode {
+        i: parent.map(|(index, _)| index),
+        n: node.name.clone(),
+        p: (parent_path.join(&node.name) != node.path).then(|| node.path.to_string_lossy().to_string()),
+        s: if node.is_dir { 0 } else { node.size },
+        u: if node.is_dir { 0 } else { node.disk_usage },
+        d: node.is_dir,
+        m: to_secs(node.modified),
+        a: to_secs(node.accessed),
+        b: to_secs(node.changed),
+        h: node.hard_link,
+        o: node.owner,
+        e: node.read_error,
+        x: node.excluded,
+    }
+}
+
+/// Rebuilds a node without its children, which arrive as later nodes.
+fn from_wire(wire: WireNode, parent: &Path) -> FileNode {
+    let path = wire.p.map_or_else(|| parent.join(&wire.n), PathBuf::from);
+    let modified = from_secs(wire.m);
+    let mut node = FileNode {
+        name: wire.n,
+        size: wire.s,
+        disk_usage: wire.u,
+        is_dir: wire.d,
+        modified,
+        accessed: from_secs(wire.a),
+        changed: from_secs(wire.b),
+        hard_link: wire.h,
+        owner: wire.o,
+        read_error: wire.e,
+        excluded: wire.x,
+        ..Default::default()
+    };
+    if !node.is_dir {
+        node.files = 1;
+        node.newest = modified;
+        node.oldest = modified;
+    }
+    node.path = path;
+    node
+}
+
+/// Sends `node` and everything below it as `Node` messages, returning the
+/// next free index.
+fn send_tree(node: &FileNode, parent: Option<(usize, &Path)>, next: usize, output: &mut impl Write) -> Result<usize> {
+    write(output, &Response::Node(to_wire(node, parent)))?;
+    let mut child_index = next + 1;
+    for child in &node.children {
+        child_index = send_tree(child, Some((next, &node.path)), child_index, output)?;
+    }
+    Ok(child_index)
+}
+
+/// Puts received nodes back together. Every node's parent comes before it,
+/// so taking them from the back completes each directory before it is
+/// added to its own parent.
+fn assemble(mut nodes: Vec<(Option<usize>, FileNode)>) -> Option<FileNode> {
+    loop {
+        let (parent, mut node) = nodes.pop()?;
+        // Children were added last first.
+        let children = std::mem::take(&mut node.children);
+        for child in children.into_iter().rev() {
+            node.add_child_totals(&child);
+            node.children.push(child);
+        }
+        if nodes.is_empty() {
+            return Some(node);
+        }
+        nodes.get_mut(parent?)?.1.children.push(node);
+    }
+}
+
+/// Serves requests from `input` until it closes. This is `gate agent`, run
+/// on the remote host with SSH carrying stdin and stdout.
+pub fn serve(input: impl BufRead, output: impl Write) -> Result<()> {
+    let mut output = BufWriter::new(output);
+    send(&mut output, &Response::Hello { version: PROTOCOL_VERSION })?;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Disks) => Response::Disks(get_disks()),
+            Ok(Request::Scan { paths, options }) => serve_scan(paths, options, &mut output)?,
+            Ok(Request::Delete { paths }) => match trash::delete_all(&paths) {
+                Ok(()) => Response::Deleted(paths),
+                Err(e) => Response::Error(format!("Failed to delete: {}", e)),
+            },
+            Err(e) => Response::Error(format!("Invalid request: {}", e)),
+        };
+        send(&mut output, &response)?;
+    }
+    Ok(())
+}
+
+fn write(output: &mut impl Write, response: &Response) -> Result<()> {
+    serde_json::to_writer(&mut *output, response)?;
+    output.write_all(b"\n")?;
+    Ok(())
+}
+
+fn send(output: &mut impl Write, response: &Response) -> Result<()> {
+    write(output, response)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Scans while forwarding throttled progress, then answers with the tree.
+fn serve_scan(paths: Vec<PathBuf>, options: ScanOptions, output: &mut impl Write) -> Result<Response> {
+    let mut scanner = MultiScanner::new(paths, options);
+    let mut last_progress = Instant::now();
+    loop {
+        match scanner.try_recv() {
+            Some(ScanMessage::Completed(root)) => {
+                let nodes = send_tree(&root, None, 0, output)?;
+                return Ok(Response::Tree { nodes });
+            }
+            Some(ScanMessage::Error(e)) => return Ok(Response::Error(e)),
+            Some(ScanMessage::Progress(p)) => {
+                if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                    last_progress = Instant::now();
+                    let progress = Response::Progress { files: p.files_scanned, bytes: p.bytes_scanned, path: p.current_path };
+                    send(output, &progress)?;
+                }
+            }
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+pub enum RemoteMessage {
+    Disks(Vec<DiskInfo>),
+    Scan(ScanMessage),
+    Deleted(Vec<PathBuf>),
+    Error(String),
+    /// The connection is gone, with the reason (often SSH's own error output).
+    Disconnected(String),
+}
+
+/// Client side of a connection to a `gate agent`. Requests are queued on the
+/// agent's stdin and answered in order through `try_recv`; dropping the
+/// agent ends the connection.
+pub struct RemoteAgent {
+    pub transport: Transport,
+    /// The ssh process; a loopback agent ends with its input.
+    child: Option<Child>,
+    stdin: Box<dyn Write + Send>,
+    rx: Receiver<RemoteMessage>,
+}
+
+impl RemoteAgent {
+    pub fn connect(transport: Transport) -> Result<Self> {
+        let (tx, rx) = unbounded();
+        let (child, stdin): (_, Box<dyn Write + Send>) = match &transport {
+            Transport::Ssh { host, command } => {
+                if host.starts_with('-') {
+                    bail!("Invalid host name '{}'", host);
+                }
+                let mut child = Command::new("ssh")
+                    // There is no terminal to answer password or host key prompts,
+                    // and nothing from the host on may be read as an ssh option.
+                    .args(["-T", "-o", "BatchMode=yes", "--", host.as_str()])
+                    .arg(format!("{} agent", command))
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("Failed to connect to {}", transport.label()))?;
+                let (Some(stdin), Some(stdout), Some(mut stderr)) = (child.stdin.take(), child.stdout.take(), child.stderr.take()) else {
+                    bail!("Failed to open pipes to the agent");
+                };
+                let stderr_reader = thread::spawn(move || {
+                    let mut text = String::new();
+                    let _ = stderr.read_to_string(&mut text);
+                    text
+                });
+                // The agent or ssh exited; whatever it printed last says why.
+                spawn_reader(stdout, tx, move || {
+                    let stderr = stderr_reader.join().unwrap_or_default();
+                    stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or_default().to_string()
+                });
+                (Some(child), Box::new(stdin))
+            }
+            Transport::Loopback => {
+                let (request_reader, request_writer) = std::io::pipe().context("Failed to open pipes to the agent")?;
+                let (response_reader, response_writer) = std::io::pipe().context("Failed to open pipes to the agent")?;
+                let agent = thread::spawn(move || serve(BufReader::new(request_reader), response_writer));
+                spawn_reader(response_reader, tx, move || match agent.join() {
+                    Ok(Err(e)) => format!("{:#}", e),
+                    _ => String::new(),
+                });
+                (None, Box::new(request_writer))
+            }
+        };
+        Ok(Self { transport, child, stdin, rx })
+    }
+
+    fn send(&mut self, request: &Request) {
+        // A write only fails once the agent is gone, which the reader reports.
+        if serde_json::to_writer(&mut self.stdin, request).is_ok() {
+            let _ = self.stdin.write_all(b"\n").and_then(|_| self.stdin.flush());
+        }
+    }
+
+    pub fn request_disks(&mut self) {
+        self.send(&Request::Disks);
+    }
+
+    pub fn scan(&mut self, paths: Vec<PathBuf>, options: ScanOptions) {
+        self.send(&Request::Scan { paths, options });
+    }
+
+    /// Moves `paths` to the trash on the remote host.
+    pub fn delete(&mut self, paths: Vec<PathBuf>) {
+        self.send(&Request::Delete { paths });
+    }
+
+    pub fn try_recv(&self) -> Option<RemoteMessage> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl Drop for RemoteAgent {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Forwards the agent's responses until it closes its output, then reports
+/// the disconnect with `last_words` as the reason if they say anything.
+fn spawn_reader(output: impl Read + Send + 'static, tx: Sender<RemoteMessage>, last_words: impl FnOnce() -> String + Send + 'static) {
+    thread::spawn(move || {
+        let reason = match read_responses(BufReader::new(output), &tx) {
+            Err(e) => format!("{:#}", e),
+            Ok(()) => Some(last_words()).filter(|w| !w.is_empty()).unwrap_or_else(|| "Connection closed".to_string()),
+        };
+        let _ = tx.send(RemoteMessage::Disconnected(reason));
+    });
+}
+
+fn read_responses(stdout: impl BufRead, tx: &Sender<RemoteMessage>) -> Result<()> {
+    let mut lines = stdout.lines();
+    let Some(first) = lines.next() else {
+        return Ok(());
+    };
+    match serde_json::from_str(&first?) {
+        Ok(Response::Hello { version }) if version == PROTOCOL_VERSION => {}
+        Ok(Response::Hello { version }) => {
+            bail!("The remote Gate speaks protocol version {}, this one version {}", version, PROTOCOL_VERSION)
+        }
+        _ => bail!("The remote command is not a Gate agent"),
+    }
+
+    let mut scan_started = None;
+    let mut nodes: Vec<(Option<usize>, FileNode)> = Vec::new();
+    for line in lines {
+        let response = serde_json::from_str(&line?).context("Invalid message from the agent")?;
+        let message = match response {
+            Response::Hello { .. } => continue,
+            Response::Disks(disks) => RemoteMessage::Disks(disks),
+            Response::Progress { files, bytes, path } => RemoteMessage::Scan(ScanMessage::Progress(ScanProgress {
+                files_scanned: files,
+                bytes_scanned: bytes,
+                current_path: path,
+                start_time: *scan_started.get_or_insert_with(Instant::now),
+            })),
+            Response::Node(wire) => {
+                let parent = wire.i;
+                let node = match parent {
+                    Some(i) => from_wire(wire, &nodes.get(i).context("Invalid message from the agent")?.1.path),
+                    None => from_wire(wire, Path::new("")),
+                };
+                nodes.push((parent, node));
+                continue;
+            }
+            Response::Tree { nodes: count } => {
+                scan_started = None;
+                let received = std::mem::take(&mut nodes);
+                let tree = (received.len() == count).then(|| assemble(received)).flatten();
+                RemoteMessage::Scan(ScanMessage::Completed(tree.context("Invalid tree from the agent")?))
+            }
+            Response::Deleted(paths) => RemoteMessage::Deleted(paths),
+            Response::Error(e) => {
+                scan_started = None;
+                RemoteMessage::Error(e)
+            }
+        };
+        if tx.send(message).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::Measure;
+    use std::fs;
+
+    /// Waits for the next answer, skipping scan progress.
+    fn receive(agent: &RemoteAgent) -> RemoteMessage {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while Instant::now() < deadline {
+            match agent.try_recv() {
+                Some(RemoteMessage::Scan(ScanMessage::Progress(_))) | None => thread::sleep(Duration::from_millis(10)),
+                Some(message) => return message,
+            }
+        }
+        panic!("no answer from the agent");
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gate-remote-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn scan_locally(path: &Path, options: ScanOptions) -> FileNode {
+        let mut scanner = MultiScanner::new(vec![path.to_path_buf()], options);
+        loop {
+            match scanner.try_recv() {
+                Some(ScanMessage::Completed(root)) => return root,
+                Some(ScanMessage::Error(e)) => panic!("{}", e),
+                _ => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+
+    fn assert_same_tree(remote: &FileNode, local: &FileNode) {
+        assert_eq!(remote.path, local.path);
+        assert_eq!(remote.name, local.name);
+        assert_eq!((remote.size, remote.disk_usage, remote.files, remote.dirs), (local.size, local.disk_usage, local.files, local.dirs));
+        assert_eq!(remote.is_dir, local.is_dir);
+        assert_eq!(remote.owner, local.owner);
+        assert_eq!(to_secs(remote.modified), to_secs(local.modified));
+        assert_eq!(remote.children.len(), local.children.len());
+        for (remote, local) in remote.children.iter().zip(&local.children) {
+            assert_same_tree(remote, local);
+        }
+    }
+
+    /// Shares shown for the children of every directory add up to it.
+    fn assert_shares_add_up(node: &FileNode) {
+        if !node.is_dir || node.excluded {
+            return;
+        }
+        for measure in [Measure::Bytes, Measure::Items] {
+            let shares: u64 = node.children.iter().map(|c| measure.of(c)).sum();
+            assert_eq!(shares, measure.of_children(node), "{:?} of {}", measure, node.path.display());
+        }
+        node.children.iter().for_each(assert_shares_add_up);
+    }
+
+    #[test]
+    fn hello_checks_the_protocol_version() {
+        let (tx, _rx) = unbounded();
+        let newer = format!("{{\"Hello\":{{\"version\":{}}}}}\n", PROTOCOL_VERSION + 1);
+        let error = read_responses(newer.as_bytes(), &tx).unwrap_err();
+        assert!(error.to_string().contains("protocol version"), "{}", error);
+
+        let error = read_responses("Welcome to the server\n".as_bytes(), &tx).unwrap_err();
+        assert!(error.to_string().contains("not a Gate agent"), "{}", error);
+
+        let current = format!("{{\"Hello\":{{\"version\":{}}}}}\n", PROTOCOL_VERSION);
+        assert!(read_responses(current.as_bytes(), &tx).is_ok());
+    }
+
+    #[test]
+    fn hosts_are_never_ssh_options() {
+        let transport = Transport::Ssh { host: "-oProxyCommand=false".to_string(), command: "gate".to_string() };
+        assert!(RemoteAgent::connect(transport).is_err());
+    }
+
+    #[test]
+    fn disks() {
+        let mut agent = RemoteAgent::connect(Transport::Loopback).unwrap();
+        agent.request_disks();
+        assert!(matches!(receive(&agent), RemoteMessage::Disks(_)));
+    }
+
+    #[test]
+    fn scan_sends_the_whole_tree() {
+        let dir = temp_dir("scan");
+        fs::write(dir.join("top.txt"), vec![0; 1000]).unwrap();
+        fs::create_dir(dir.join("empty")).unwrap();
+        // Deeper than serde_json would parse as one nested message.
+        let mut deep = dir.join("deep");
+        for level in 0..200 {
+            deep = deep.join(level.to_string());
+        }
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(deep.join("bottom.txt"), vec![0; 10]).unwrap();
+        // Excluded placeholders must count the same on both sides.
+        fs::create_dir_all(dir.join("skipped-dir/inner")).unwrap();
+        fs::write(dir.join("skipped.log"), vec![0; 100]).unwrap();
+        let options = ScanOptions { exclusions: vec!["skipped*".to_string()], ..Default::default() };
+
+        let mut agent = RemoteAgent::connect(Transport::Loopback).unwrap();
+        agent.scan(vec![dir.clone()], options.clone());
+        let RemoteMessage::Scan(ScanMessage::Completed(remote)) = receive(&agent) else {
+            panic!("expected a tree");
+        };
+        let mut local = scan_locally(&dir, options);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(remote.size, 1010);
+        assert_eq!(remote.files, 2);
+        // Scans finish in any order; compare them sorted the same way.
+        fn sort(node: &mut FileNode) {
+            node.children.sort_by(|a, b| a.name.cmp(&b.name));
+            node.children.iter_mut().for_each(sort);
+        }
+        let mut remote = remote;
+        sort(&mut remote);
+        sort(&mut local);
+        assert_same_tree(&remote, &local);
+        assert_shares_add_up(&remote);
+        assert_shares_add_up(&local);
+    }
+
+    #[test]
+    fn invalid_trees_are_rejected() {
+        let node = |parent: Option<usize>, name: &str| (parent, FileNode { name: name.to_string(), is_dir: true, ..Default::default() });
+        assert!(assemble(vec![node(None, "root"), node(Some(0), "a"), node(Some(1), "b")]).is_some());
+        // A parent must come before its children.
+        assert!(assemble(vec![node(None, "root"), node(Some(2), "a"), node(Some(0), "b")]).is_none());
+        assert!(assemble(vec![node(None, "root"), node(None, "a")]).is_none());
+        assert!(assemble(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn delete_moves_to_the_trash() {
+        let dir = temp_dir("delete");
+        let file = dir.join("unwanted.txt");
+        fs::write(&file, "x").unwrap();
+
+        let mut agent = RemoteAgent::connect(Transport::Loopback).unwrap();
+        agent.delete(vec![file.clone()]);
+        let message = receive(&agent);
+        let _ = fs::remove_dir_all(&dir);
+        match message {
+            RemoteMessage::Deleted(paths) => assert_eq!(paths, vec![file.clone()]),
+            RemoteMessage::Error(e) => panic!("{}", e),
+            _ => panic!("expected Deleted"),
+        }
+        assert!(!file.exists());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::core::filetypes::TypeBreakdown;
+use crate::core::scanner::FileNode;
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Directories below this share of the report root are folded into one
+/// "smaller items" entry, which keeps reports of whole disks small.
+const MIN_NODE_SHARE: f64 = 1.0 / 20_000.0;
+const MAX_CHILDREN: usize = 200;
+const TOP_FILES: usize = 100;
+
+const TEMPLATE: &str = include_str!("report.html");
+
+#[derive(Serialize)]
+struct ReportNode {
+    /// Name, size, is directory; short keys keep big reports compact.
+    n: String,
+    s: u64,
+    d: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    c: Vec<ReportNode>,
+}
+
+#[derive(Serialize)]
+struct ReportFile {
+    path: String,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct ReportType {
+    label: String,
+    bytes: u64,
+    files: u64,
+}
+
+#[derive(Serialize)]
+struct ReportData {
+    title: String,
+    generated: String,
+    roots: Vec<String>,
+    total_size: u64,
+    files: u64,
+    directories: u64,
+    excluded: u64,
+    tree: ReportNode,
+    top_files: Vec<ReportFile>,
+    categories: Vec<ReportType>,
+    extensions: Vec<ReportType>,
+}
+
+/// Renders `root` as a single self-contained HTML page with a collapsible
+/// tree, a treemap, the largest files and a type breakdown.
+pub fn render_html(root: &FileNode) -> Result<String> {
+    let min_size = (root.size as f64 * MIN_NODE_SHARE) as u64;
+    let breakdown = TypeBreakdown::compute(root);
+    let (mut directories, mut excluded) = (0, 0);
+    count_nodes(root, &mut directories, &mut excluded);
+
+    let mut files: Vec<&FileNode> = Vec::new();
+    root.visit_files(&mut |f| files.push(f));
+    files.sort_unstable_by_key(|f| std::cmp::Reverse(f.size));
+    files.truncate(TOP_FILES);
+
+    let roots = if root.path.as_os_str().is_empty() {
+        root.children.iter().map(|c| c.path.display().to_string()).collect()
+    } else {
+        vec![root.path.display().to_string()]
+    };
+    let to_types = |stats: &[crate::core::filetypes::TypeStat]| {
+        stats.iter()
+            .map(|s| ReportType { label: s.filter.label(), bytes: s.bytes, files: s.files })
+            .collect::<Vec<_>>()
+    };
+    let data = ReportData {
+        title: roots.join(", "),
+        generated: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        roots,
+        total_size: root.size,
+        files: breakdown.total_files,
+        directories,
+        excluded,
+        tree: report_node(root, min_size),
+        top_files: files.iter().map(|f| ReportFile { path: f.path.display().to_string(), size: f.size }).collect(),
+        categories: to_types(&breakdown.by_category),
+        extensions: to_types(&breakdown.by_extension[..breakdown.by_extension.len().min(30)]),
+    };
+
+    // `</` would end the script element the data is embedded in.
+    let json = serde_json::to_string(&data)?.replace("</", "<\\/");
+    Ok(TEMPLATE.replace("/*GATE_REPORT_DATA*/null", &json))
+}
+
+pub fn export_html(root: &FileNode, path: &Path) -> Result<()> {
+    let html = render_html(root)?;
+    fs::write(path, html).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn report_node(node: &FileNode, min_size: u64) -> ReportNode {
+    let mut children = Vec::new();
+    let mut rest = (0u64, 0usize);
+    let mut by_size: Vec<&FileNode> = node.children.iter().collect();
+    by_size.sort_by_key(|c| std::cmp::Reverse(c.size));
+    for child in by_size {
+        if children.len() < MAX_CHILDREN && child.size >= min_size {
+            children.push(report_node(child, min_size));
+        } else {
+            rest.0 += child.size;
+            rest.1 += 1;
+        }
+    }
+    if rest.1 > 0 {
+        children.push(ReportNode { n: format!("({} smaller items)", rest.1), s: rest.0, d: false, c: Vec::new() });
+    }
+    ReportNode {
+        n: if node.excluded { format!("{} (excluded)", node.name) } else { node.name.clone() },
+        s: node.size,
+        d: node.is_dir,
+        c: children,
+    }
+}
+
+fn count_nodes(node: &FileNode, directories: &mut u64, excluded: &mut u64) {
+    if node.excluded {
+        *excluded += 1;
+    } else if node.is_dir {
+        *directories += 1;
+    }
+    for child in &node.children {
+        count_nodes(child, directories, excluded);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::core::age::age_days;
+use crate::core::scanner::FileNode;
+use crate::core::search::{parse_size, Query};
+use crate::core::settings::Settings;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Newest rule file format this build understands.
+pub const RULES_VERSION: u32 = 1;
+
+/// A cleanup rule written by the user, e.g.
+///
+/// ```toml
+/// [[rule]]
+/// name = "Old core dumps"
+/// query = "name:*.core type:file"
+/// older_than_days = 7
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserRule {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Same syntax as the search box.
+    pub query: String,
+    /// Minimum age by modification time (newest file for directories).
+    #[serde(default)]
+    pub older_than_days: Option<u64>,
+    /// Minimum size such as `500M` or `1G`.
+    #[serde(default)]
+    pub min_size: Option<String>,
+}
+
+/// A rule file, stored as TOML so it can be checked into a repository and
+/// shared. Gate reads `rules.toml` from its config directory by default.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub version: u32,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<UserRule>,
+}
+
+impl RuleSet {
+    pub fn default_path() -> Option<PathBuf> {
+        Settings::config_dir().map(|d| d.join("rules.toml"))
+    }
+
+    /// Loads a rule file and checks that every rule compiles.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid rule file {}", path.display()))
+    }
+
+    /// Parses the TOML of a rule file and checks that every rule compiles.
+    pub fn parse(text: &str) -> Result<Self> {
+        let set: RuleSet = toml::from_str(text)?;
+        if set.version > RULES_VERSION {
+            bail!("Rule format version {} is newer than this Gate supports ({})", set.version, RULES_VERSION);
+        }
+        for rule in &set.rules {
+            CompiledRule::new(rule).with_context(|| format!("Invalid rule '{}'", rule.name))?;
+        }
+        Ok(set)
+    }
+
+    /// Loads the default rule file; a missing file is an empty set.
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(RuleSet { version: RULES_VERSION, rules: Vec::new() }),
+        }
+    }
+
+    /// Evaluates every rule against the tree. Rules that match nothing are
+    /// left out; results are sorted by size, largest first. Scanned
+    /// locations themselves never match, only what is inside them.
+    pub fn evaluate(&self, root: &FileNode, now: SystemTime) -> Result<Vec<RuleMatch>> {
+        // A combined scan (empty path) holds one scanned location per child.
+        let scan_roots = if root.path.as_os_str().is_empty() { root.children.iter().collect() } else { vec![root] };
+        let mut matches = Vec::new();
+        for rule in &self.rules {
+            let compiled = CompiledRule::new(rule).with_context(|| format!("Invalid rule '{}'", rule.name))?;
+            let mut paths = Vec::new();
+            let mut size = 0;
+            for child in scan_roots.iter().flat_map(|r| &r.children) {
+                compiled.collect(child, now, &mut paths, &mut size);
+            }
+            if !paths.is_empty() {
+                matches.push(RuleMatch { rule: rule.clone(), paths, size });
+            }
+        }
+        matches.sort_by_key(|m| std::cmp::Reverse(m.size));
+        Ok(matches)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RuleMatch {
+    pub rule: UserRule,
+    pub paths: Vec<PathBuf>,
+    pub size: u64,
+}
+
+/// Paths of several matches with duplicates and paths nested inside another
+/// match removed, ready to be trashed in one go.
+pub fn distinct_paths<'a>(matches: impl IntoIterator<Item = &'a RuleMatch>) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = matches.into_iter().flat_map(|m| m.paths.iter().cloned()).collect();
+    paths.sort();
+    paths.dedup();
+    // Sorted order puts a parent right before the paths inside it.
+    let mut distinct: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if !distinct.last().is_some_and(|last| path.starts_with(last)) {
+            distinct.push(path);
+        }
+    }
+    distinct
+}
+
+struct CompiledRule {
+    query: Query,
+    older_than_days: Option<u64>,
+    min_size: u64,
+}
+
+impl CompiledRule {
+    fn new(rule: &UserRule) -> Result<Self> {
+        let query = Query::parse(&rule.query)?;
+        if query.is_empty() && rule.older_than_days.is_none() && rule.min_size.is_none() {
+            bail!("A rule needs a query, older_than_days or min_size");
+        }
+        Ok(Self {
+            query,
+            older_than_days: rule.older_than_days,
+            min_size: rule.min_size.as_deref().map(parse_size).transpose()?.unwrap_or(0),
+        })
+    }
+
+    fn matches(&self, node: &FileNode, now: SystemTime) -> bool {
+        let modified = if node.is_dir { node.newest } else { node.modified };
+        !node.excluded
+            && node.size >= self.min_size
+            && self.older_than_days.is_none_or(|days| age_days(modified, now).is_some_and(|d| d >= days))
+            && self.query.matches(node)
+    }
+
+    /// A matching directory is taken whole, so nothing below it is counted twice.
+    fn collect(&self, node: &FileNode, now: SystemTime, paths: &mut Vec<PathBuf>, size: &mut u64) {
+        if self.matches(node, now) {
+            paths.push(node.path.clone());
+            *size += node.size;
+            return;
+        }
+        for child in &node.children {
+            self.collect(child, now, paths, size);
+        }
+    }
+}
+
+const EXAMPLE_RULES: &str = r#"# Gate cleanup rules. Each rule is a search query (same syntax as the search
+# box) plus optional age and size limits. Share this file with your team and
+# point Gate at it with `gate clean --rules FILE`.
+version = 1
+
+[[rule]]
+name = "Old core dumps"
+description = "Crash dumps nobody looked at for a week"
+query = "re:^core(\\.\\d+)?$ type:file"
+older_than_days = 7
+
+[[rule]]
+name = "Large temporary folders"
+query = "name:tmp_* type:dir"
+min_size = "1G"
+"#;
+
+/// Writes a commented example rule file to `path`, refusing to overwrite.
+pub fn write_example(path: &Path) -> Result<()> {
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(path, EXAMPLE_RULES).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn file(path: &str, size: u64, modified: SystemTime) -> FileNode {
+        let path = PathBuf::from(path);
+        FileNode {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            path,
+            size,
+            files: 1,
+            modified: Some(modified),
+            newest: Some(modified),
+            oldest: Some(modified),
+            ..Default::default()
+        }
+    }
+
+    fn dir(path: &str, children: Vec<FileNode>) -> FileNode {
+        let path = PathBuf::from(path);
+        let mut node = FileNode {
+            name: path.file_name().map_or(String::new(), |n| n.to_string_lossy().to_string()),
+            path,
+            is_dir: true,
+            ..Default::default()
+        };
+        for child in &children {
+            node.add_child_totals(child);
+        }
+        node.children = children;
+        node
+    }
+
+    fn matched_paths(rules: &str, root: &FileNode, now: SystemTime) -> Vec<PathBuf> {
+        let set = RuleSet::parse(rules).unwrap();
+        let mut paths: Vec<PathBuf> = set.evaluate(root, now).unwrap().into_iter().flat_map(|m| m.paths).collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn age_size_and_glob() {
+        let now = SystemTime::UNIX_EPOCH + 1000 * DAY;
+        let root = dir("/data", vec![
+            file("/data/old.core", 10, now - 30 * DAY),
+            file("/data/new.core", 10, now - DAY),
+            file("/data/big.iso", 2_000_000_000, now),
+            file("/data/small.iso", 1_000, now),
+        ]);
+        let rules = r#"
+            version = 1
+            [[rule]]
+            name = "Old dumps"
+            query = "*.core"
+            older_than_days = 7
+            [[rule]]
+            name = "Big images"
+            query = "ext:iso"
+            min_size = "1G"
+        "#;
+        assert_eq!(matched_paths(rules, &root, now), vec![PathBuf::from("/data/big.iso"), PathBuf::from("/data/old.core")]);
+    }
+
+    #[test]
+    fn matching_directory_is_taken_whole() {
+        let now = SystemTime::UNIX_EPOCH + 1000 * DAY;
+        let root = dir("/data", vec![dir("/data/tmp_build", vec![file("/data/tmp_build/tmp_x", 5, now)])]);
+        let rules = "version = 1\n[[rule]]\nname = \"tmp\"\nquery = \"name:tmp_*\"\n";
+        assert_eq!(matched_paths(rules, &root, now), vec![PathBuf::from("/data/tmp_build")]);
+    }
+
+    #[test]
+    fn scanned_locations_never_match() {
+        let now = SystemTime::UNIX_EPOCH + 1000 * DAY;
+        let logs = dir("/logs", vec![file("/logs/app.log", 5, now)]);
+        let cache = dir("/cache", vec![file("/cache/blob", 5, now)]);
+        let rules = "version = 1\n[[rule]]\nname = \"everything\"\nquery = \"\"\nmin_size = \"1\"\n";
+        let single = matched_paths(rules, &logs, now);
+        assert_eq!(single, vec![PathBuf::from("/logs/app.log")]);
+        let combined = FileNode::combined(vec![logs, cache]);
+        assert_eq!(matched_paths(rules, &combined, now), vec![PathBuf::from("/cache/blob"), PathBuf::from("/logs/app.log")]);
+    }
+
+    #[test]
+    fn version_check() {
+        assert!(RuleSet::parse("version = 1").unwrap().rules.is_empty());
+        let newer = format!("version = {}", RULES_VERSION + 1);
+        assert!(RuleSet::parse(&newer).is_err());
+        assert!(RuleSet::parse("").is_err());
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(RuleSet::parse("version = 1\n[[rule]]\nname = \"empty\"\nquery = \"\"\n").is_err());
+        assert!(RuleSet::parse("version = 1\n[[rule]]\nname = \"bad size\"\nquery = \"x\"\nmin_size = \"lots\"\n").is_err());
+        assert!(RuleSet::parse("version = 1\n[[rule]]\nname = \"bad query\"\nquery = \"size:\"\n").is_err());
+        assert!(RuleSet::parse(EXAMPLE_RULES).is_ok());
+    }
+}
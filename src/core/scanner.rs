@@ -1,7 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::thread;
+use std::time::SystemTime;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use std::sync::{Arc, Mutex};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default)]
 pub struct FileNode {
@@ -9,9 +12,239 @@ pub struct FileNode {
     pub path: PathBuf,
     pub size: u64,
     pub is_dir: bool,
+    pub modified: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    /// Last status change (ctime): contents, permissions, owner or links.
+    pub changed: Option<SystemTime>,
+    /// For directories, the newest and oldest modification time of any file below.
+    pub newest: Option<SystemTime>,
+    pub oldest: Option<SystemTime>,
+    /// Skipped because it matched an exclusion rule; size is unknown, not zero.
+    pub excluded: bool,
+    /// Space allocated on disk. Differs from `size` for sparse and compressed
+    /// files and for small files that still take a whole block.
+    pub disk_usage: u64,
+    /// Inode number of a file with more than one hard link.
+    pub hard_link: Option<u64>,
+    /// The entry could not be read, so sizes below it may be incomplete.
+    pub read_error: bool,
+    /// Files at or below this node (a file counts itself) and directories
+    /// below it. Excluded entries count themselves only.
+    pub files: u64,
+    pub dirs: u64,
+    /// Owning user id, where the platform has one.
+    pub owner: Option<u32>,
+    /// In directory order; views sort as they need.
     pub children: Vec<FileNode>,
 }
 
+impl FileNode {
+    /// Virtual root shown when several locations are scanned together.
+    pub fn combined(roots: Vec<FileNode>) -> FileNode {
+        let mut node = FileNode {
+            name: format!("{} locations", roots.len()),
+            is_dir: true,
+            ..Default::default()
+        };
+        for root in &roots {
+            node.add_child_totals(root);
+        }
+        node.children = roots;
+        node
+    }
+
+    /// Everything below a directory, or 1 for a file.
+    pub fn items(&self) -> u64 {
+        self.files + self.dirs
+    }
+
+    /// Adds `child`'s sizes, counts and times to this directory's totals.
+    /// Excluded placeholders stand for something skipped and add nothing, so
+    /// a tree counts the same however it was built.
+    pub(crate) fn add_child_totals(&mut self, child: &FileNode) {
+        if child.excluded {
+            return;
+        }
+        self.size += child.size;
+        self.disk_usage += child.disk_usage;
+        self.files += child.files;
+        self.dirs += child.dirs + u64::from(child.is_dir);
+        self.absorb_times(child);
+    }
+
+    /// Widens this directory's newest/oldest range to include `child`.
+    fn absorb_times(&mut self, child: &FileNode) {
+        self.newest = self.newest.max(child.newest);
+        self.oldest = match (self.oldest, child.oldest) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Lowercased extension of a file, without the dot.
+    pub fn extension(&self) -> Option<String> {
+        if self.is_dir {
+            return None;
+        }
+        self.path.extension().map(|e| e.to_string_lossy().to_lowercase())
+    }
+
+    /// Calls `f` for every file (not directory) below this node.
+    pub fn visit_files<'a>(&'a self, f: &mut impl FnMut(&'a FileNode)) {
+        if !self.is_dir {
+            f(self);
+        }
+        for child in &self.children {
+            child.visit_files(f);
+        }
+    }
+
+    pub fn find(&self, path: &Path) -> Option<&FileNode> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children.iter()
+            .filter(|c| path.starts_with(&c.path))
+            .find_map(|c| c.find(path))
+    }
+
+    /// Detaches the node at `path` and subtracts its totals from every ancestor.
+    pub fn remove_descendant(&mut self, path: &Path) -> Option<FileNode> {
+        let idx = self.children.iter().position(|c| path.starts_with(&c.path))?;
+        let removed = if self.children[idx].path == path {
+            Some(self.children.remove(idx))
+        } else {
+            self.children[idx].remove_descendant(path)
+        };
+        if let Some(node) = &removed {
+            self.size = self.size.saturating_sub(node.size);
+            self.disk_usage = self.disk_usage.saturating_sub(node.disk_usage);
+            self.files = self.files.saturating_sub(node.files);
+            self.dirs = self.dirs.saturating_sub(node.dirs + u64::from(node.is_dir));
+        }
+        removed
+    }
+}
+
+/// User-configurable scan behaviour, saved with the settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Glob patterns. Patterns containing `/` match the full path,
+    /// others match the file or directory name anywhere in the tree.
+    pub exclusions: Vec<String>,
+    /// Do not descend into other filesystems mounted below the scan root.
+    pub one_file_system: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            exclusions: vec!["/proc".to_string(), "/sys".to_string(), "/dev".to_string()],
+            one_file_system: false,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Checks that an exclusion is a valid glob before it is saved; invalid
+    /// ones would otherwise never match.
+    pub fn check_exclusion(pattern: &str) -> Result<(), String> {
+        Pattern::new(pattern.trim_end_matches('/')).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+struct ScanRules {
+    path_patterns: Vec<Pattern>,
+    name_patterns: Vec<Pattern>,
+    root_device: Option<u64>,
+}
+
+impl ScanRules {
+    fn new(options: &ScanOptions, root: &Path) -> Self {
+        let mut rules = ScanRules {
+            path_patterns: Vec::new(),
+            name_patterns: Vec::new(),
+            root_device: if options.one_file_system { device_of(root) } else { None },
+        };
+        for pattern in &options.exclusions {
+            let Ok(compiled) = Pattern::new(pattern.trim_end_matches('/')) else {
+                continue;
+            };
+            if pattern.contains('/') {
+                rules.path_patterns.push(compiled);
+            } else {
+                rules.name_patterns.push(compiled);
+            }
+        }
+        rules
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        if self.path_patterns.iter().any(|p| p.matches_path(path)) {
+            return true;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if self.name_patterns.iter().any(|p| p.matches(&name)) {
+            return true;
+        }
+        self.root_device.is_some() && path.is_dir() && device_of(path) != self.root_device
+    }
+}
+
+#[cfg(unix)]
+pub fn device_of(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    path.symlink_metadata().ok().map(|m| m.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device_of(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Allocated bytes and, for files with several hard links, the inode.
+#[cfg(unix)]
+fn allocation(metadata: &std::fs::Metadata) -> (u64, Option<u64>) {
+    use std::os::unix::fs::MetadataExt;
+    let hard_link = (metadata.nlink() > 1).then(|| metadata.ino());
+    (metadata.blocks() * 512, hard_link)
+}
+
+#[cfg(not(unix))]
+fn allocation(metadata: &std::fs::Metadata) -> (u64, Option<u64>) {
+    (metadata.len(), None)
+}
+
+#[cfg(unix)]
+fn owner(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn owner(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// Login name of a user id, as `ls -l` shows it.
+#[cfg(unix)]
+pub fn user_name(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+    let status = unsafe { libc::getpwuid_r(uid, &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result) };
+    if status != 0 || result.is_null() {
+        return None;
+    }
+    Some(unsafe { std::ffi::CStr::from_ptr(passwd.pw_name) }.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+pub fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
 #[derive(Clone, Debug)]
 pub struct ScanProgress {
     pub files_scanned: u64,
@@ -42,15 +275,16 @@ pub struct Scanner {
 }
 
 impl Scanner {
-    pub fn new(path: PathBuf) -> Self {
+    pub fn new(path: PathBuf, options: ScanOptions) -> Self {
         let (tx, rx) = unbounded();
         thread::spawn(move || {
             let progress = Arc::new(Mutex::new(ScanProgress::default()));
             let tx_clone = tx.clone();
             let progress_clone = progress.clone();
+            let rules = ScanRules::new(&options, &path);
             
             match std::panic::catch_unwind(move || {
-                scan_recursive(&path, &tx_clone, &progress_clone)
+                scan_recursive(&path, &rules, &tx_clone, &progress_clone)
             }) {
                 Ok(root) => {
                     let _ = tx.send(ScanMessage::Completed(root));
@@ -68,8 +302,120 @@ impl Scanner {
     }
 }
 
+#[cfg(unix)]
+fn changed(metadata: &std::fs::Metadata) -> Option<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+    let secs = u64::try_from(metadata.ctime()).ok()?;
+    let nanos = u32::try_from(metadata.ctime_nsec()).unwrap_or(0);
+    Some(std::time::UNIX_EPOCH + std::time::Duration::new(secs, nanos))
+}
+
+#[cfg(not(unix))]
+fn changed(_metadata: &std::fs::Metadata) -> Option<SystemTime> {
+    None
+}
+
+/// Scans several roots concurrently and reports them as one scan. With more
+/// than one root the result is a virtual root holding each scanned root.
+pub struct MultiScanner {
+    paths: Vec<PathBuf>,
+    scanners: Vec<Scanner>,
+    progress: Vec<ScanProgress>,
+    results: Vec<Option<Result<FileNode, String>>>,
+    start_time: std::time::Instant,
+    /// Roots that failed while others completed, as "path: error". They
+    /// appear in the tree as unreadable placeholders.
+    pub failures: Vec<String>,
+}
+
+impl MultiScanner {
+    pub fn new(paths: Vec<PathBuf>, options: ScanOptions) -> Self {
+        let count = paths.len();
+        Self {
+            scanners: paths.iter().map(|p| Scanner::new(p.clone(), options.clone())).collect(),
+            paths,
+            progress: vec![ScanProgress::default(); count],
+            results: (0..count).map(|_| None).collect(),
+            start_time: std::time::Instant::now(),
+            failures: Vec::new(),
+        }
+    }
+
+    fn combined_progress(&self, current_path: String) -> ScanProgress {
+        ScanProgress {
+            files_scanned: self.progress.iter().map(|p| p.files_scanned).sum(),
+            bytes_scanned: self.progress.iter().map(|p| p.bytes_scanned).sum(),
+            current_path,
+            start_time: self.start_time,
+        }
+    }
+
+    /// Drains every scanner and returns at most one combined message.
+    pub fn try_recv(&mut self) -> Option<ScanMessage> {
+        let mut latest_path = None;
+        for (i, scanner) in self.scanners.iter().enumerate() {
+            while let Some(msg) = scanner.try_recv() {
+                match msg {
+                    ScanMessage::Progress(p) => {
+                        latest_path = Some(p.current_path.clone());
+                        self.progress[i] = p;
+                    }
+                    ScanMessage::Completed(node) => self.results[i] = Some(Ok(node)),
+                    ScanMessage::Error(e) => self.results[i] = Some(Err(e)),
+                }
+            }
+        }
+
+        if self.results.iter().all(|r| r.is_some()) && !self.results.is_empty() {
+            let mut roots = Vec::new();
+            for (path, result) in self.paths.iter().zip(self.results.drain(..).flatten()) {
+                match result {
+                    Ok(node) => roots.push(node),
+                    Err(e) => {
+                        self.failures.push(format!("{}: {}", path.display(), e));
+                        roots.push(failed_node(path.clone()));
+                    }
+                }
+            }
+            if self.failures.len() == roots.len() {
+                return Some(ScanMessage::Error(std::mem::take(&mut self.failures).join("\n")));
+            }
+            let root = if roots.len() == 1 { roots.remove(0) } else { FileNode::combined(roots) };
+            return Some(ScanMessage::Completed(root));
+        }
+
+        latest_path.map(|path| ScanMessage::Progress(self.combined_progress(path)))
+    }
+}
+
+/// Placeholder kept in the tree so it is obvious something was skipped.
+fn excluded_node(path: PathBuf) -> FileNode {
+    let is_dir = path.is_dir();
+    FileNode {
+        name: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+        is_dir,
+        files: u64::from(!is_dir),
+        excluded: true,
+        path,
+        ..Default::default()
+    }
+}
+
+/// Placeholder for a root whose scan failed, so a combined view does not
+/// silently leave it out.
+fn failed_node(path: PathBuf) -> FileNode {
+    FileNode {
+        name: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+        is_dir: true,
+        read_error: true,
+        path,
+        ..Default::default()
+    }
+}
+
 fn scan_recursive(
     path: &PathBuf, 
+    rules: &ScanRules,
     tx: &Sender<ScanMessage>, 
     progress: &Arc<Mutex<ScanProgress>>
 ) -> FileNode {
@@ -78,6 +424,18 @@ fn scan_recursive(
         path: path.clone(),
         size: 0,
         is_dir: path.is_dir(),
+        modified: None,
+        accessed: None,
+        changed: None,
+        newest: None,
+        oldest: None,
+        excluded: false,
+        disk_usage: 0,
+        hard_link: None,
+        read_error: false,
+        files: 0,
+        dirs: 0,
+        owner: None,
         children: vec![],
     };
 
@@ -90,20 +448,36 @@ fn scan_recursive(
     }
 
     if node.is_dir {
-        if let Ok(entries) = std::fs::read_dir(path) {
+        node.owner = path.symlink_metadata().ok().and_then(|m| owner(&m));
+        let entries = std::fs::read_dir(path);
+        node.read_error = entries.is_err();
+        if let Ok(entries) = entries {
             for entry in entries.flatten() {
                 let child_path = entry.path();
                 if child_path.is_symlink() { continue; }
+                if rules.is_excluded(&child_path) {
+                    node.children.push(excluded_node(child_path));
+                    continue;
+                }
                 
-                let child_node = scan_recursive(&child_path, tx, progress);
-                node.size += child_node.size;
+                let child_node = scan_recursive(&child_path, rules, tx, progress);
+                node.add_child_totals(&child_node);
                 node.children.push(child_node);
             }
         }
-        node.children.sort_by(|a, b| b.size.cmp(&a.size));
     } else {
-        if let Ok(metadata) = path.metadata() {
+        node.files = 1;
+        let metadata = path.metadata();
+        node.read_error = metadata.is_err();
+        if let Ok(metadata) = metadata {
             node.size = metadata.len();
+            (node.disk_usage, node.hard_link) = allocation(&metadata);
+            node.owner = owner(&metadata);
+            node.modified = metadata.modified().ok();
+            node.accessed = metadata.accessed().ok();
+            node.changed = changed(&metadata);
+            node.newest = node.modified;
+            node.oldest = node.modified;
             if let Ok(mut p) = progress.lock() {
                 p.bytes_scanned += node.size;
             }
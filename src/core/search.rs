@@ -0,0 +1,326 @@
+use crate::core::filetypes::{FileCategory, TypeFilter};
+use crate::core::scanner::FileNode;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Local, NaiveDate, TimeZone};
+use glob::Pattern;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Cmp {
+    /// Splits a leading comparison operator off `s`; no operator means `default`.
+    fn split(s: &str, default: Cmp) -> (Cmp, &str) {
+        for (prefix, cmp) in [(">=", Cmp::Ge), ("<=", Cmp::Le), (">", Cmp::Gt), ("<", Cmp::Lt), ("=", Cmp::Eq)] {
+            if let Some(rest) = s.strip_prefix(prefix) {
+                return (cmp, rest);
+            }
+        }
+        (default, s)
+    }
+
+    fn test(&self, ord: Ordering) -> bool {
+        match self {
+            Cmp::Lt => ord == Ordering::Less,
+            Cmp::Le => ord != Ordering::Greater,
+            Cmp::Eq => ord == Ordering::Equal,
+            Cmp::Ge => ord != Ordering::Less,
+            Cmp::Gt => ord == Ordering::Greater,
+        }
+    }
+}
+
+const FILTER_KEYS: [&str; 11] = ["name", "re", "regex", "path", "ext", "size", "modified", "mtime", "accessed", "atime", "type"];
+
+#[derive(Clone, Debug)]
+enum Term {
+    Name(String),
+    NameGlob(Pattern),
+    NameRegex(Regex),
+    PathGlob(Pattern),
+    Ext(Vec<String>),
+    Size(Cmp, u64),
+    Modified(Cmp, SystemTime),
+    Accessed(Cmp, SystemTime),
+    IsDir(bool),
+    Type(TypeFilter),
+}
+
+impl Term {
+    fn parse(token: &str) -> Result<Term> {
+        if let Some(re) = token.strip_prefix('/').and_then(|t| t.strip_suffix('/')) {
+            return Ok(Term::NameRegex(Regex::new(&format!("(?i){}", re))?));
+        }
+        // Names may contain colons themselves (`12:30.log`, `C:`), so only
+        // known keys start a filter.
+        let filter = token.split_once(':').filter(|(key, _)| FILTER_KEYS.contains(key));
+        let Some((key, value)) = filter else {
+            if token.contains(['*', '?', '[']) {
+                return Ok(Term::NameGlob(glob_pattern(token)?));
+            }
+            return Ok(Term::Name(token.to_lowercase()));
+        };
+        if value.is_empty() {
+            bail!("Missing value for '{}:'", key);
+        }
+        Ok(match key {
+            "name" => Term::NameGlob(glob_pattern(value)?),
+            "re" | "regex" => Term::NameRegex(Regex::new(&format!("(?i){}", value))?),
+            "path" => Term::PathGlob(glob_pattern(value)?),
+            "ext" => Term::Ext(value.split(',').map(|e| e.trim_start_matches('.').to_lowercase()).collect()),
+            "size" => {
+                let (cmp, rest) = Cmp::split(value, Cmp::Ge);
+                Term::Size(cmp, parse_size(rest)?)
+            }
+            "modified" | "mtime" => {
+                let (cmp, rest) = Cmp::split(value, Cmp::Eq);
+                Term::Modified(cmp, parse_date(rest)?)
+            }
+            "accessed" | "atime" => {
+                let (cmp, rest) = Cmp::split(value, Cmp::Eq);
+                Term::Accessed(cmp, parse_date(rest)?)
+            }
+            "type" => match value {
+                "dir" | "folder" => Term::IsDir(true),
+                "file" => Term::IsDir(false),
+                other => Term::Type(TypeFilter::Category(parse_category(other)?)),
+            },
+            _ => unreachable!("'{}' is not in FILTER_KEYS", key),
+        })
+    }
+
+    fn matches(&self, node: &FileNode) -> bool {
+        match self {
+            Term::Name(s) => node.name.to_lowercase().contains(s),
+            Term::NameGlob(p) => p.matches_with(&node.name, glob_options()),
+            Term::NameRegex(re) => re.is_match(&node.name),
+            Term::PathGlob(p) => p.matches_path_with(&node.path, glob_options()),
+            Term::Ext(exts) => node.extension().is_some_and(|e| exts.contains(&e)),
+            Term::Size(cmp, size) => cmp.test(node.size.cmp(size)),
+            Term::Modified(cmp, t) => date_matches(*cmp, modified_time(node), *t),
+            Term::Accessed(cmp, t) => date_matches(*cmp, node.accessed, *t),
+            Term::IsDir(is_dir) => node.is_dir == *is_dir,
+            Term::Type(filter) => filter.matches(node),
+        }
+    }
+}
+
+/// A parsed search such as `ext:mp4 size:>1G modified:<2024-01-01 path:*/cache/*`.
+/// All terms must match; a leading `-` negates a term.
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    terms: Vec<(bool, Term)>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query> {
+        let mut terms = Vec::new();
+        for token in input.split_whitespace() {
+            let (negate, token) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, token),
+            };
+            let term = Term::parse(token).with_context(|| format!("Invalid search term '{}'", token))?;
+            terms.push((negate, term));
+        }
+        Ok(Query { terms })
+    }
+
+    pub fn with_type_filter(mut self, filter: Option<&TypeFilter>) -> Self {
+        if let Some(filter) = filter {
+            self.terms.push((false, Term::Type(filter.clone())));
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, node: &FileNode) -> bool {
+        self.terms.iter().all(|(negate, term)| term.matches(node) != *negate)
+    }
+}
+
+/// Result of evaluating a query over the whole tree once.
+#[derive(Clone, Debug, Default)]
+pub struct SearchResult {
+    /// Every matching node, largest first.
+    pub matches: Vec<PathBuf>,
+    /// Matching nodes plus all of their ancestors, i.e. what the tree should draw.
+    pub visible: HashSet<PathBuf>,
+}
+
+impl SearchResult {
+    pub fn evaluate(root: &FileNode, query: &Query) -> Self {
+        let mut result = SearchResult::default();
+        let mut matches = Vec::new();
+        collect(root, query, &mut matches, &mut result.visible);
+        matches.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+        result.matches = matches.into_iter().map(|(_, path)| path).collect();
+        result
+    }
+}
+
+fn collect(node: &FileNode, query: &Query, matches: &mut Vec<(u64, PathBuf)>, visible: &mut HashSet<PathBuf>) -> bool {
+    let is_match = query.matches(node);
+    if is_match {
+        matches.push((node.size, node.path.clone()));
+    }
+    let mut any_child = false;
+    for child in &node.children {
+        any_child |= collect(child, query, matches, visible);
+    }
+    let is_visible = is_match || any_child;
+    if is_visible {
+        visible.insert(node.path.clone());
+    }
+    is_visible
+}
+
+fn glob_options() -> glob::MatchOptions {
+    glob::MatchOptions {
+        case_sensitive: false,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    }
+}
+
+fn glob_pattern(s: &str) -> Result<Pattern> {
+    Pattern::new(s).map_err(|e| anyhow!("{}", e))
+}
+
+/// Directories have no useful mtime of their own, so they use their newest file.
+fn modified_time(node: &FileNode) -> Option<SystemTime> {
+    if node.is_dir { node.newest } else { node.modified }
+}
+
+/// Dates compare by calendar day, so `modified:2024-01-01` matches the whole day.
+fn date_matches(cmp: Cmp, time: Option<SystemTime>, day_start: SystemTime) -> bool {
+    let Some(time) = time else {
+        return false;
+    };
+    let day_end = day_start + std::time::Duration::from_secs(24 * 60 * 60);
+    let ord = if time < day_start {
+        Ordering::Less
+    } else if time >= day_end {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    };
+    cmp.test(ord)
+}
+
+/// Parses sizes like `500`, `10K`, `1.5G` or `2TB` (decimal units, as shown in the UI).
+pub fn parse_size(s: &str) -> Result<u64> {
+    let s = s.trim().to_uppercase();
+    let s = s.strip_suffix('B').unwrap_or(&s);
+    let (num, mult) = match s.chars().last() {
+        Some('K') => (&s[..s.len() - 1], 1e3),
+        Some('M') => (&s[..s.len() - 1], 1e6),
+        Some('G') => (&s[..s.len() - 1], 1e9),
+        Some('T') => (&s[..s.len() - 1], 1e12),
+        _ => (s, 1.0),
+    };
+    let value: f64 = num.parse().map_err(|_| anyhow!("Invalid size '{}'", s))?;
+    Ok((value * mult) as u64)
+}
+
+fn parse_date(s: &str) -> Result<SystemTime> {
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| anyhow!("Invalid date '{}', expected YYYY-MM-DD", s))?;
+    let midnight = date.and_hms_opt(0, 0, 0).context("Invalid date")?;
+    let local = Local.from_local_datetime(&midnight).earliest().context("Invalid local date")?;
+    Ok(local.into())
+}
+
+fn parse_category(s: &str) -> Result<FileCategory> {
+    let wanted = s.replace(['-', '_'], "").to_lowercase();
+    FileCategory::ALL.iter()
+        .find(|c| c.label().replace(' ', "").to_lowercase().starts_with(&wanted))
+        .copied()
+        .ok_or_else(|| anyhow!("Unknown type '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn file(name: &str, size: u64, modified: Option<SystemTime>) -> FileNode {
+        FileNode {
+            name: name.to_string(),
+            path: PathBuf::from("/data").join(name),
+            size,
+            modified,
+            ..Default::default()
+        }
+    }
+
+    fn matches(query: &str, node: &FileNode) -> bool {
+        Query::parse(query).unwrap().matches(node)
+    }
+
+    #[test]
+    fn size_comparisons() {
+        let node = file("movie.mp4", 1_500_000_000, None);
+        assert!(matches("size:1G", &node));
+        assert!(matches("size:>1G", &node));
+        assert!(matches("size:<=1.5GB", &node));
+        assert!(!matches("size:<1G", &node));
+        assert!(!matches("size:=1G", &node));
+        assert!(matches("-size:>2G", &node));
+        assert_eq!(parse_size("10k").unwrap(), 10_000);
+        assert_eq!(parse_size("2TB").unwrap(), 2_000_000_000_000);
+    }
+
+    #[test]
+    fn date_comparisons() {
+        let day = parse_date("2024-03-10").unwrap();
+        let node = file("notes.txt", 1, Some(day + Duration::from_secs(3600)));
+        assert!(matches("modified:2024-03-10", &node));
+        assert!(matches("mtime:>2024-03-09", &node));
+        assert!(matches("modified:<2024-03-11", &node));
+        assert!(!matches("modified:>=2024-03-11", &node));
+        assert!(!matches("modified:2024-03-10", &file("unknown.txt", 1, None)));
+    }
+
+    #[test]
+    fn glob_and_regex() {
+        let node = file("Backup-2023.tar.gz", 1, None);
+        assert!(matches("*.tar.gz", &node));
+        assert!(matches("name:backup-*", &node));
+        assert!(matches("/^backup-\\d{4}/", &node));
+        assert!(matches("re:\\.GZ$", &node));
+        assert!(matches("path:/data/*", &node));
+        assert!(!matches("*.zip", &node));
+        assert!(matches("ext:gz,zip", &node));
+    }
+
+    #[test]
+    fn names_with_colons_are_plain_terms() {
+        assert!(matches("12:30.log", &file("backup-12:30.log", 1, None)));
+        assert!(matches("C:", &file("C:", 1, None)));
+        assert!(!matches("12:30", &file("1230.log", 1, None)));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Query::parse("size:").is_err());
+        assert!(Query::parse("size:huge").is_err());
+        assert!(Query::parse("modified:yesterday").is_err());
+        assert!(Query::parse("/(/").is_err());
+        assert!(Query::parse("name:[").is_err());
+        assert!(Query::parse("type:nonsense").is_err());
+        assert!(Query::parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::core::alerts::AlertSettings;
+use crate::core::scanner::{FileNode, ScanOptions};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const MAX_RECENT: usize = 10;
+
+/// Everything Gate remembers between launches, stored as TOML under the
+/// XDG config directory (`~/.config/gate/settings.toml`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub scan: ScanOptions,
+    /// Most recently scanned folders and mounts, newest first.
+    pub recent_locations: Vec<String>,
+    pub alerts: AlertSettings,
+    pub appearance: Appearance,
+    /// Locations of the last scan, offered again on the home screen.
+    pub last_roots: Vec<String>,
+    pub top_files: TopFilesLayout,
+    pub tree: TreeLayout,
+    pub remote: RemoteSettings,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub dark_mode: bool,
+    pub ui_scale: f32,
+    /// Default zoom of the tree view.
+    pub zoom: f32,
+    pub measure: Measure,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            dark_mode: true,
+            ui_scale: 1.35,
+            zoom: 1.0,
+            measure: Measure::Bytes,
+        }
+    }
+}
+
+/// What bars, percentages and charts compare. Counting items finds folders
+/// that hold millions of small files, which cost inodes and backup time
+/// more than space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Measure {
+    Bytes,
+    Items,
+}
+
+impl Measure {
+    pub fn label(self) -> &'static str {
+        match self {
+            Measure::Bytes => "Bytes",
+            Measure::Items => "Items",
+        }
+    }
+
+    /// A node's share of its parent; a directory counts as an item itself
+    /// and an excluded placeholder, like in the parent's totals, as nothing.
+    pub fn of(self, node: &FileNode) -> u64 {
+        match self {
+            _ if node.excluded => 0,
+            Measure::Bytes => node.size,
+            Measure::Items => node.items() + u64::from(node.is_dir),
+        }
+    }
+
+    /// What the shares of a directory's children add up to.
+    pub fn of_children(self, node: &FileNode) -> u64 {
+        match self {
+            Measure::Bytes => node.size,
+            Measure::Items => node.items(),
+        }
+    }
+}
+
+/// Columns of the tree table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeColumn {
+    Name,
+    Size,
+    Allocated,
+    Items,
+    Percent,
+    Modified,
+    Owner,
+}
+
+/// How the tree orders siblings. Scans keep directory order; sorting is
+/// only applied when drawing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TreeLayout {
+    pub sort_column: TreeColumn,
+    pub ascending: bool,
+}
+
+impl Default for TreeLayout {
+    fn default() -> Self {
+        Self {
+            sort_column: TreeColumn::Size,
+            ascending: false,
+        }
+    }
+}
+
+/// Hosts scanned over SSH, see `core::remote`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteSettings {
+    /// How Gate is started on the hosts, e.g. `~/bin/gate`.
+    pub command: String,
+    /// Most recently connected hosts, newest first.
+    pub recent_hosts: Vec<String>,
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            command: "gate".to_string(),
+            recent_hosts: Vec::new(),
+        }
+    }
+}
+
+/// Columns of the file tables that can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileColumn {
+    Path,
+    Size,
+    Modified,
+    Type,
+}
+
+/// Sorting and row limit of the top files table. Column widths are kept by
+/// egui itself, see `ui_state.ron` next to this file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TopFilesLayout {
+    pub limit: usize,
+    pub sort_column: FileColumn,
+    pub ascending: bool,
+}
+
+impl Default for TopFilesLayout {
+    fn default() -> Self {
+        Self {
+            limit: 1000,
+            sort_column: FileColumn::Size,
+            ascending: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn config_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("gate"))
+    }
+
+    fn path() -> Option<PathBuf> {
+        Self::config_dir().map(|d| d.join("settings.toml"))
+    }
+
+    /// Loads the saved settings, falling back to defaults if there are none
+    /// or the file cannot be parsed. For read-only use; see `try_load`.
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|e| {
+            eprintln!("gate: {:#}, using default settings", e);
+            Self::default()
+        })
+    }
+
+    /// Loads the saved settings, with defaults only if there is no file yet.
+    /// A file that cannot be read or parsed is an error, so that whoever
+    /// saves settings can leave it alone instead of overwriting it.
+    pub fn try_load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        toml::from_str(&text).with_context(|| format!("Invalid settings file {}", path.display()))
+    }
+
+    /// Window geometry and other egui state (such as table column widths)
+    /// are persisted by eframe into this file.
+    pub fn ui_state_path() -> Option<PathBuf> {
+        Self::config_dir().map(|d| d.join("ui_state.ron"))
+    }
+
+    /// Back to defaults, keeping the history of scanned locations.
+    pub fn reset(&mut self) {
+        *self = Settings {
+            recent_locations: std::mem::take(&mut self.recent_locations),
+            last_roots: std::mem::take(&mut self.last_roots),
+            remote: RemoteSettings {
+                recent_hosts: std::mem::take(&mut self.remote.recent_hosts),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+    }
+
+    pub fn push_recent(&mut self, location: String) {
+        self.recent_locations.retain(|l| *l != location);
+        self.recent_locations.insert(0, location);
+        self.recent_locations.truncate(MAX_RECENT);
+    }
+
+    pub fn push_recent_host(&mut self, host: String) {
+        self.remote.recent_hosts.retain(|h| *h != host);
+        self.remote.recent_hosts.insert(0, host);
+        self.remote.recent_hosts.truncate(MAX_RECENT);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("No config directory available")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let text = toml::to_string_pretty(self)?;
+        fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
@@ -0,0 +1,364 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use anyhow::{anyhow, bail, Context, Result};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use walkdir::WalkDir;
+use xxhash_rust::xxh3::Xxh3;
+use crate::core::disk::DiskInfo;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferKind {
+    Move,
+    Archive,
+}
+
+impl TransferKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransferKind::Move => "Move",
+            TransferKind::Archive => "Archive to .tar.zst",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TransferProgress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub current_path: String,
+    pub start_time: std::time::Instant,
+}
+
+impl TransferProgress {
+    pub fn new(bytes_total: u64) -> Self {
+        Self {
+            bytes_done: 0,
+            bytes_total,
+            current_path: String::new(),
+            start_time: std::time::Instant::now(),
+        }
+    }
+
+    pub fn ratio(&self) -> f32 {
+        if self.bytes_total == 0 {
+            return 1.0;
+        }
+        (self.bytes_done as f32 / self.bytes_total as f32).min(1.0)
+    }
+}
+
+pub enum TransferMessage {
+    Progress(TransferProgress),
+    /// The source has been copied, verified and removed.
+    Completed,
+    Error(String),
+}
+
+/// Copies (or archives) a file or directory to another disk on a background
+/// thread, verifies the result and only then removes the source.
+pub struct Transfer {
+    pub source: PathBuf,
+    pub kind: TransferKind,
+    rx: Receiver<TransferMessage>,
+}
+
+impl Transfer {
+    pub fn new(source: PathBuf, source_size: u64, dest_dir: PathBuf, kind: TransferKind) -> Self {
+        let (tx, rx) = unbounded();
+        let src = source.clone();
+        thread::spawn(move || {
+            let mut progress = TransferProgress::new(source_size);
+            let result = match kind {
+                TransferKind::Move => move_to(&src, &dest_dir, &tx, &mut progress),
+                TransferKind::Archive => archive_to(&src, &dest_dir, &tx, &mut progress),
+            };
+            let msg = match result {
+                Ok(()) => TransferMessage::Completed,
+                Err(e) => TransferMessage::Error(format!("{:#}", e)),
+            };
+            let _ = tx.send(msg);
+        });
+        Self { source, kind, rx }
+    }
+
+    pub fn try_recv(&self) -> Option<TransferMessage> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Checks that `disk` has room for `required` bytes before a transfer starts.
+pub fn check_free_space(disk: &DiskInfo, required: u64) -> Result<()> {
+    if disk.available_space < required {
+        bail!(
+            "Not enough free space on {}: {} needed, {} available",
+            disk.mount_point,
+            humansize::format_size(required, humansize::DECIMAL),
+            humansize::format_size(disk.available_space, humansize::DECIMAL),
+        );
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> Result<&std::ffi::OsStr> {
+    path.file_name().ok_or_else(|| anyhow!("Cannot transfer {}", path.display()))
+}
+
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn remove_source(path: &Path) -> Result<()> {
+    remove(path).with_context(|| format!("Copied, but failed to remove {}", path.display()))
+}
+
+/// Runs `copy`, which writes `dest`, and removes whatever it left behind
+/// when it fails so that a retry does not stop at "already exists".
+fn or_remove_partial(dest: &Path, copy: impl FnOnce() -> Result<()>) -> Result<()> {
+    let result = copy();
+    if result.is_err() && fs::symlink_metadata(dest).is_ok() {
+        let _ = remove(dest);
+    }
+    result
+}
+
+/// Walks `src` without following symbolic links, not even when `src` is one,
+/// so a link is transferred as a link rather than as what it points to.
+fn walk(src: &Path) -> WalkDir {
+    WalkDir::new(src).follow_links(false).follow_root_links(false)
+}
+
+/// Refuses sources holding FIFOs, sockets or device files. They cannot be
+/// copied, and removing the source afterwards would lose them.
+fn check_transferable(src: &Path) -> Result<()> {
+    for entry in walk(src) {
+        let entry = entry?;
+        let file_type = entry.file_type();
+        if !file_type.is_dir() && !file_type.is_file() && !file_type.is_symlink() {
+            bail!("{} is a special file (FIFO, socket or device) and cannot be transferred", entry.path().display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> Result<()> {
+    let target = fs::read_link(src).with_context(|| format!("Failed to read link {}", src.display()))?;
+    std::os::unix::fs::symlink(&target, dest).with_context(|| format!("Failed to create link {}", dest.display()))
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, _dest: &Path) -> Result<()> {
+    bail!("Cannot copy the symbolic link {} on this platform", src.display())
+}
+
+fn move_to(
+    src: &Path,
+    dest_dir: &Path,
+    tx: &Sender<TransferMessage>,
+    progress: &mut TransferProgress,
+) -> Result<()> {
+    let dest = dest_dir.join(file_name(src)?);
+    if fs::symlink_metadata(&dest).is_ok() {
+        bail!("{} already exists", dest.display());
+    }
+    check_transferable(src)?;
+
+    or_remove_partial(&dest, || {
+        // Applied last, as a read-only directory could not be filled.
+        let mut dir_permissions = Vec::new();
+        for entry in walk(src) {
+            let entry = entry?;
+            let rel = entry.path().strip_prefix(src)?;
+            let target = if rel.as_os_str().is_empty() { dest.clone() } else { dest.join(rel) };
+            let file_type = entry.file_type();
+
+            if file_type.is_dir() {
+                fs::create_dir_all(&target)
+                    .with_context(|| format!("Failed to create {}", target.display()))?;
+                dir_permissions.push((target, entry.metadata()?.permissions()));
+            } else if file_type.is_file() {
+                copy_verified(entry.path(), &target, tx, progress)?;
+            } else if file_type.is_symlink() {
+                copy_symlink(entry.path(), &target)?;
+            }
+        }
+        for (dir, permissions) in dir_permissions.into_iter().rev() {
+            fs::set_permissions(&dir, permissions)
+                .with_context(|| format!("Failed to set permissions of {}", dir.display()))?;
+        }
+        Ok(())
+    })?;
+
+    remove_source(src)
+}
+
+/// Copies a single file while hashing it, then re-reads the copy and compares.
+fn copy_verified(
+    src: &Path,
+    dest: &Path,
+    tx: &Sender<TransferMessage>,
+    progress: &mut TransferProgress,
+) -> Result<()> {
+    progress.current_path = src.to_string_lossy().to_string();
+
+    let mut reader = BufReader::new(File::open(src).with_context(|| format!("Failed to open {}", src.display()))?);
+    let mut writer = BufWriter::new(File::create(dest).with_context(|| format!("Failed to create {}", dest.display()))?);
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        writer.write_all(&buf[..n])?;
+        progress.bytes_done += n as u64;
+        let _ = tx.send(TransferMessage::Progress(progress.clone()));
+    }
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+
+    let metadata = fs::metadata(src)?;
+    fs::set_permissions(dest, metadata.permissions())?;
+    if let Ok(modified) = metadata.modified() {
+        let _ = file.set_modified(modified);
+    }
+
+    let mut copied = Xxh3::new();
+    let mut reader = BufReader::new(File::open(dest)?);
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        copied.update(&buf[..n]);
+    }
+    if copied.digest128() != hasher.digest128() {
+        bail!("Verification failed for {}", dest.display());
+    }
+    Ok(())
+}
+
+struct ProgressReader<'a, R> {
+    inner: R,
+    tx: &'a Sender<TransferMessage>,
+    progress: &'a mut TransferProgress,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.bytes_done += n as u64;
+        let _ = self.tx.send(TransferMessage::Progress(self.progress.clone()));
+        Ok(n)
+    }
+}
+
+fn archive_to(
+    src: &Path,
+    dest_dir: &Path,
+    tx: &Sender<TransferMessage>,
+    progress: &mut TransferProgress,
+) -> Result<()> {
+    let name = file_name(src)?;
+    let mut archive_name = name.to_os_string();
+    archive_name.push(".tar.zst");
+    let dest = dest_dir.join(archive_name);
+    if fs::symlink_metadata(&dest).is_ok() {
+        bail!("{} already exists", dest.display());
+    }
+    check_transferable(src)?;
+
+    or_remove_partial(&dest, || write_archive(src, name, &dest, tx, progress))?;
+
+    remove_source(src)
+}
+
+fn write_archive(
+    src: &Path,
+    name: &std::ffi::OsStr,
+    dest: &Path,
+    tx: &Sender<TransferMessage>,
+    progress: &mut TransferProgress,
+) -> Result<()> {
+    let file = File::create(dest).with_context(|| format!("Failed to create {}", dest.display()))?;
+    let encoder = zstd::Encoder::new(BufWriter::new(file), 3)?;
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+
+    let mut expected_files = 0u64;
+    let mut expected_links = 0u64;
+    let mut expected_bytes = 0u64;
+    for entry in walk(src) {
+        let entry = entry?;
+        let rel = Path::new(name).join(entry.path().strip_prefix(src)?);
+        let file_type = entry.file_type();
+
+        if file_type.is_dir() {
+            builder.append_dir(&rel, entry.path())?;
+        } else if file_type.is_file() {
+            progress.current_path = entry.path().to_string_lossy().to_string();
+            let metadata = entry.metadata()?;
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&metadata);
+            let reader = ProgressReader {
+                inner: BufReader::new(File::open(entry.path())?),
+                tx,
+                progress,
+            };
+            builder.append_data(&mut header, &rel, reader)?;
+            expected_files += 1;
+            expected_bytes += metadata.len();
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())
+                .with_context(|| format!("Failed to read link {}", entry.path().display()))?;
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&entry.metadata()?);
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, &rel, &target)?;
+            expected_links += 1;
+        }
+    }
+
+    let encoder = builder.into_inner()?;
+    let file = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+
+    verify_archive(dest, expected_files, expected_links, expected_bytes)
+        .with_context(|| format!("Verification failed for {}", dest.display()))
+}
+
+/// Decompresses the archive and checks that every file made it in, in full,
+/// along with every symbolic link.
+fn verify_archive(path: &Path, expected_files: u64, expected_links: u64, expected_bytes: u64) -> Result<()> {
+    let decoder = zstd::Decoder::new(File::open(path)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut files = 0u64;
+    let mut links = 0u64;
+    let mut bytes = 0u64;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_file() {
+            files += 1;
+            bytes += io::copy(&mut entry, &mut io::sink())?;
+        } else if entry_type.is_symlink() {
+            links += 1;
+        }
+    }
+    if files != expected_files || links != expected_links || bytes != expected_bytes {
+        bail!(
+            "expected {} files ({} bytes) and {} links, found {} ({} bytes) and {}",
+            expected_files, expected_bytes, expected_links, files, bytes, links
+        );
+    }
+    Ok(())
+}
+
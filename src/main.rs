@@ -1,20 +1,47 @@
 mod app;
+mod cli;
 mod core;
 mod ui;
 
-use app::GateApp;
+use app::{GateApp, DEFAULT_WINDOW_SIZE};
+use core::settings::Settings;
 use eframe::egui;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|a| cli::is_command(a)) {
+        return cli::run(&args);
+    }
+    // Folders open in the GUI; anything else, such as arguments a desktop
+    // environment adds, is ignored.
+    let mut locations = Vec::new();
+    for arg in args {
+        let path = std::path::PathBuf::from(&arg);
+        if path.is_dir() {
+            locations.push(path);
+        } else {
+            eprintln!("gate: ignoring '{}', which is neither a command nor a folder", arg);
+        }
+    }
 
-fn main() -> eframe::Result<()> {
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1280.0, 720.0]) // 16:9 aspect ratio
+            .with_inner_size(DEFAULT_WINDOW_SIZE) // 16:9 aspect ratio
             .with_title("Gate"),
+        persistence_path: Settings::ui_state_path(),
         ..Default::default()
     };
-    eframe::run_native(
+    let result = eframe::run_native(
         "Gate",
         native_options,
-        Box::new(|cc| Ok(Box::new(GateApp::new(cc)))),
-    )
+        Box::new(|cc| Ok(Box::new(GateApp::new(cc, locations)))),
+    );
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("gate: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
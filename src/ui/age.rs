@@ -0,0 +1,136 @@
+use egui::{Ui, Color32, Vec2, Rounding, RichText, Sense};
+use egui_extras::{Column, TableBuilder};
+use crate::core::age::{age_histogram, format_time, stale_files, AgeBucket, AgeField};
+use crate::core::scanner::FileNode;
+use crate::core::settings::Measure;
+use crate::ui::tree::{item_context_menu, TreeAction};
+use humansize::{format_size, DECIMAL};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+struct StaleEntry {
+    path: PathBuf,
+    size: u64,
+    time: Option<SystemTime>,
+}
+
+/// Age histogram and "stale files" finder.
+pub struct AgeView {
+    field: AgeField,
+    min_age_days: u64,
+    min_size_mb: u64,
+    histogram: Vec<AgeBucket>,
+    stale: Vec<StaleEntry>,
+    built_for: Option<(PathBuf, u64, AgeField, u64, u64)>,
+}
+
+impl AgeView {
+    pub fn new() -> Self {
+        Self {
+            field: AgeField::Accessed,
+            min_age_days: 365,
+            min_size_mb: 100,
+            histogram: Vec::new(),
+            stale: Vec::new(),
+            built_for: None,
+        }
+    }
+
+    fn rebuild(&mut self, root: &FileNode) {
+        let now = SystemTime::now();
+        self.histogram = age_histogram(root, self.field, now);
+        self.stale = stale_files(root, self.field, self.min_age_days, self.min_size_mb * 1_000_000, now)
+            .into_iter()
+            .map(|f| StaleEntry { path: f.path.clone(), size: f.size, time: self.field.of(f) })
+            .collect();
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, root: &FileNode, measure: Measure, selected_path: &mut Option<PathBuf>) -> Option<TreeAction> {
+        let key = (root.path.clone(), root.size, self.field, self.min_age_days, self.min_size_mb);
+        if self.built_for.as_ref() != Some(&key) {
+            self.rebuild(root);
+            self.built_for = Some(key);
+        }
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading(RichText::new("File Age").strong().size(20.0));
+            ui.add_space(20.0);
+            ui.selectable_value(&mut self.field, AgeField::Accessed, AgeField::Accessed.label());
+            ui.selectable_value(&mut self.field, AgeField::Modified, AgeField::Modified.label());
+        });
+        ui.add_space(10.0);
+
+        let value = |bucket: &AgeBucket| match measure {
+            Measure::Bytes => bucket.bytes,
+            Measure::Items => bucket.files,
+        };
+        let largest = self.histogram.iter().map(value).max().unwrap_or(0).max(1);
+        for bucket in &self.histogram {
+            ui.horizontal(|ui| {
+                ui.add_sized([90.0, 18.0], egui::Label::new(RichText::new(bucket.label).strong()));
+                let (rect, _) = ui.allocate_at_least(Vec2::new(300.0, 14.0), Sense::hover());
+                let painter = ui.painter();
+                painter.rect_filled(rect, Rounding::same(4.0), Color32::from_gray(40));
+                let fill_width = (rect.width() * (value(bucket) as f32 / largest as f32)).max(1.0);
+                painter.rect_filled(
+                    egui::Rect::from_min_size(rect.min, Vec2::new(fill_width, rect.height())),
+                    Rounding::same(4.0),
+                    Color32::from_rgb(100, 149, 237),
+                );
+                ui.add_space(8.0);
+                ui.label(RichText::new(format!("{:>10}", format_size(bucket.bytes, DECIMAL))).monospace());
+                ui.label(RichText::new(format!("{} files", bucket.files)).weak());
+            });
+        }
+
+        ui.add_space(16.0);
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Stale files:").strong());
+            ui.label(format!("not {} in", self.field.label().to_lowercase()));
+            ui.add(egui::DragValue::new(&mut self.min_age_days).range(0..=36500).suffix(" days"));
+            ui.label("and at least");
+            ui.add(egui::DragValue::new(&mut self.min_size_mb).range(0..=10_000_000).suffix(" MB"));
+            let total: u64 = self.stale.iter().map(|e| e.size).sum();
+            ui.label(RichText::new(format!("{} files, {}", self.stale.len(), format_size(total, DECIMAL))).weak());
+        });
+        ui.add_space(6.0);
+
+        let mut action = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 6.0;
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::remainder().at_least(200.0).clip(true))
+            .column(Column::auto().at_least(90.0))
+            .column(Column::auto().at_least(140.0))
+            .header(row_height, |mut header| {
+                header.col(|ui| { ui.strong("Path"); });
+                header.col(|ui| { ui.strong("Size"); });
+                header.col(|ui| { ui.strong(self.field.label()); });
+            })
+            .body(|body| {
+                body.rows(row_height, self.stale.len(), |mut row| {
+                    let entry = &self.stale[row.index()];
+                    let is_selected = selected_path.as_ref() == Some(&entry.path);
+                    row.col(|ui| {
+                        let response = ui.selectable_label(is_selected, entry.path.to_string_lossy());
+                        item_context_menu(&response, &entry.path, &mut action);
+                        if response.clicked() {
+                            *selected_path = Some(entry.path.clone());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(format_size(entry.size, DECIMAL)).monospace());
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(format_time(entry.time)).monospace().weak());
+                    });
+                });
+            });
+
+        action
+    }
+}
@@ -0,0 +1,85 @@
+use egui::{Ui, RichText};
+use crate::core::alerts::{AlertSettings, Threshold};
+use crate::core::disk::DiskInfo;
+
+/// "Low space alerts" section of the settings window. Holds the half-filled
+/// per-mount override row between frames.
+pub struct AlertSettingsEditor {
+    mount: String,
+    threshold: Threshold,
+}
+
+impl AlertSettingsEditor {
+    pub fn new() -> Self {
+        Self {
+            mount: String::new(),
+            threshold: Threshold::FreePercent(10.0),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, alerts: &mut AlertSettings, disks: &[DiskInfo]) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut alerts.enabled, "Warn below");
+            ui.add_enabled_ui(alerts.enabled, |ui| threshold_editor(ui, "default_threshold", &mut alerts.default));
+        });
+        ui.checkbox(&mut alerts.notify, "Desktop notification when a disk runs low");
+
+        ui.add_space(4.0);
+        ui.label("Per-disk overrides:");
+        let mut remove = None;
+        for (mount, threshold) in &alerts.mounts {
+            ui.horizontal(|ui| {
+                if ui.small_button("✕").clicked() {
+                    remove = Some(mount.clone());
+                }
+                ui.label(RichText::new(mount).monospace());
+                ui.label(RichText::new(threshold.label()).weak());
+            });
+        }
+        if let Some(mount) = remove {
+            alerts.mounts.remove(&mount);
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("alert_mount")
+                .selected_text(if self.mount.is_empty() { "Mount…" } else { &self.mount })
+                .show_ui(ui, |ui| {
+                    for disk in disks {
+                        ui.selectable_value(&mut self.mount, disk.mount_point.clone(), &disk.mount_point);
+                    }
+                });
+            threshold_editor(ui, "new_threshold", &mut self.threshold);
+            if ui.add_enabled(!self.mount.is_empty(), egui::Button::new("Add")).clicked() {
+                alerts.mounts.insert(std::mem::take(&mut self.mount), self.threshold);
+            }
+        });
+    }
+}
+
+/// Value plus a "% / GB" unit switch.
+fn threshold_editor(ui: &mut Ui, id: &str, threshold: &mut Threshold) {
+    let is_percent = matches!(threshold, Threshold::FreePercent(_));
+    match threshold {
+        Threshold::FreePercent(percent) => {
+            ui.add(egui::DragValue::new(percent).range(0.0..=100.0).speed(0.5));
+        }
+        Threshold::FreeBytes(bytes) => {
+            let mut gb = *bytes as f64 / 1e9;
+            if ui.add(egui::DragValue::new(&mut gb).range(0.0..=1e6).speed(0.5)).changed() {
+                *bytes = (gb * 1e9) as u64;
+            }
+        }
+    }
+    egui::ComboBox::from_id_source(id)
+        .width(48.0)
+        .selected_text(if is_percent { "%" } else { "GB" })
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(is_percent, "%").clicked() && !is_percent {
+                *threshold = Threshold::FreePercent(10.0);
+            }
+            if ui.selectable_label(!is_percent, "GB").clicked() && is_percent {
+                *threshold = Threshold::FreeBytes(10_000_000_000);
+            }
+        });
+    ui.label("free");
+}
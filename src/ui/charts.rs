@@ -1,125 +1,129 @@
-use egui::{Ui, Color32, Stroke, Vec2, Shape, Rounding};
+use egui::{Ui, Color32, Vec2, Rounding, RichText, Sense};
+use crate::core::filetypes::{TypeBreakdown, TypeFilter, TypeStat};
 use crate::core::scanner::FileNode;
+use crate::core::settings::Measure;
 use humansize::{format_size, DECIMAL};
-use std::f32::consts::TAU;
+use std::path::PathBuf;
 
-struct ChartEntry {
-    name: String,
-    size: u64,
-    percentage: f32,
-    color: Color32,
+const COLORS: [Color32; 8] = [
+    Color32::from_rgb(100, 149, 237), // Cornflower Blue
+    Color32::from_rgb(255, 127, 80),  // Coral
+    Color32::from_rgb(60, 179, 113),  // Medium Sea Green
+    Color32::from_rgb(255, 215, 0),   // Gold
+    Color32::from_rgb(138, 43, 226),  // Blue Violet
+    Color32::from_rgb(255, 105, 180), // Hot Pink
+    Color32::from_rgb(0, 206, 209),   // Dark Turquoise
+    Color32::from_rgb(210, 105, 30),  // Chocolate
+];
+
+/// File type / extension breakdown of the scanned tree.
+pub struct TypeBreakdownView {
+    by_extension: bool,
+    max_rows: usize,
+    breakdown: TypeBreakdown,
+    built_for: Option<(PathBuf, u64)>,
 }
 
-pub fn show_charts(ui: &mut Ui, node: &FileNode) {
-    ui.vertical(|ui| {
+impl TypeBreakdownView {
+    pub fn new() -> Self {
+        Self {
+            by_extension: false,
+            max_rows: 50,
+            breakdown: TypeBreakdown::default(),
+            built_for: None,
+        }
+    }
+
+    /// Returns a filter when a row is clicked, so the tree can be narrowed to it.
+    pub fn ui(&mut self, ui: &mut Ui, root: &FileNode, measure: Measure) -> Option<TypeFilter> {
+        let key = (root.path.clone(), root.size);
+        if self.built_for.as_ref() != Some(&key) {
+            self.breakdown = TypeBreakdown::compute(root);
+            self.built_for = Some(key);
+        }
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading(RichText::new("File Types").strong().size(20.0));
+            ui.add_space(20.0);
+            ui.selectable_value(&mut self.by_extension, false, "By category");
+            ui.selectable_value(&mut self.by_extension, true, "By extension");
+            ui.add_space(10.0);
+            ui.label(RichText::new(format!(
+                "{} files, {}",
+                self.breakdown.total_files,
+                format_size(self.breakdown.total_bytes, DECIMAL)
+            )).weak());
+        });
         ui.add_space(10.0);
-        ui.heading(egui::RichText::new("Disk Usage Distribution").strong().size(20.0));
-        ui.add_space(20.0);
 
-        let total_size = node.size as f64;
-        if total_size == 0.0 || node.children.is_empty() {
+        if self.breakdown.total_files == 0 {
             ui.label("No data to display");
-            return;
+            return None;
         }
 
-        ui.horizontal_top(|ui| {
-            // Left side: Pie Chart
-            let chart_size = 300.0;
-            let (rect, response) = ui.allocate_at_least(Vec2::splat(chart_size), egui::Sense::hover());
-            
-            let mut children: Vec<_> = node.children.iter().collect();
-            children.sort_by(|a, b| b.size.cmp(&a.size));
-
-            let top_n = 8;
-            let mut entries: Vec<ChartEntry> = Vec::new();
-            let colors = [
-                Color32::from_rgb(100, 149, 237), // Cornflower Blue
-                Color32::from_rgb(255, 127, 80),  // Coral
-                Color32::from_rgb(60, 179, 113),  // Medium Sea Green
-                Color32::from_rgb(255, 215, 0),    // Gold
-                Color32::from_rgb(138, 43, 226),  // Blue Violet
-                Color32::from_rgb(255, 105, 180), // Hot Pink
-                Color32::from_rgb(0, 206, 209),   // Dark Turquoise
-                Color32::from_rgb(210, 105, 30),  // Chocolate
-            ];
+        let stats = if self.by_extension { &self.breakdown.by_extension } else { &self.breakdown.by_category };
+        let mut stats: Vec<&TypeStat> = stats.iter().collect();
+        if measure == Measure::Items {
+            stats.sort_by_key(|s| std::cmp::Reverse(s.files));
+        }
+        let largest = stats.first().map_or(1, |s| stat_value(s, measure).max(1));
+        let total = match measure {
+            Measure::Bytes => self.breakdown.total_bytes,
+            Measure::Items => self.breakdown.total_files,
+        }.max(1);
+        let mut clicked = None;
 
-            let mut accounted_size = 0u64;
-            for (i, child) in children.iter().take(top_n).enumerate() {
-                entries.push(ChartEntry {
-                    name: child.name.clone(),
-                    size: child.size,
-                    percentage: child.size as f32 / total_size as f32,
-                    color: colors[i % colors.len()],
-                });
-                accounted_size += child.size;
-            }
+        egui::ScrollArea::vertical()
+            .id_source("type_breakdown_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (i, stat) in stats.iter().take(self.max_rows).enumerate() {
+                    if type_row(ui, stat, measure, largest, total, COLORS[i % COLORS.len()]) {
+                        clicked = Some(stat.filter.clone());
+                    }
+                }
+                if stats.len() > self.max_rows {
+                    ui.add_space(6.0);
+                    ui.label(RichText::new(format!("…and {} more", stats.len() - self.max_rows)).weak());
+                }
+            });
 
-            let other_size = node.size.saturating_sub(accounted_size);
-            if other_size > 0 {
-                entries.push(ChartEntry {
-                    name: "Others".to_string(),
-                    size: other_size,
-                    percentage: other_size as f32 / total_size as f32,
-                    color: Color32::from_gray(100),
-                });
-            }
+        clicked
+    }
+}
 
-            // Draw Pie Chart
-            let painter = ui.painter_at(rect);
-            let center = rect.center();
-            let radius = rect.width() / 2.5;
-            let mut start_angle = -TAU / 4.0;
+fn stat_value(stat: &TypeStat, measure: Measure) -> u64 {
+    match measure {
+        Measure::Bytes => stat.bytes,
+        Measure::Items => stat.files,
+    }
+}
 
-            for (i, entry) in entries.iter().enumerate() {
-                let sweep = entry.percentage * TAU;
-                if sweep > 0.001 {
-                    let mut points = vec![center];
-                    let n_points = (sweep * 50.0).max(10.0) as i32;
-                    for step in 0..=n_points {
-                        let angle = start_angle + sweep * (step as f32 / n_points as f32);
-                        points.push(center + Vec2::new(angle.cos(), angle.sin()) * radius);
-                    }
-                    
-                    let mut fill_color = entry.color;
-                    if response.hover_pos().map_or(false, |pos| {
-                        let to_pos = pos - center;
-                        let dist = to_pos.length();
-                        let angle = to_pos.y.atan2(to_pos.x);
-                        let mut normalized_angle = angle;
-                        while normalized_angle < start_angle { normalized_angle += TAU; }
-                        while normalized_angle > start_angle + sweep { normalized_angle -= TAU; }
-                        dist <= radius && normalized_angle >= start_angle && normalized_angle <= start_angle + sweep
-                    }) {
-                        fill_color = Color32::from_rgb(
-                            (fill_color.r() as u16 + 30).min(255) as u8,
-                            (fill_color.g() as u16 + 30).min(255) as u8,
-                            (fill_color.b() as u16 + 30).min(255) as u8,
-                        );
-                        
-                        egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), response.id.with(i), |ui: &mut egui::Ui| {
-                            ui.label(format!("{}: {} ({:.1}%)", entry.name, format_size(entry.size, DECIMAL), entry.percentage * 100.0));
-                        });
-                    }
+fn type_row(ui: &mut Ui, stat: &TypeStat, measure: Measure, largest: u64, total: u64, color: Color32) -> bool {
+    let value = stat_value(stat, measure);
+    let share = value as f32 / total as f32;
+    let response = ui.horizontal(|ui| {
+        ui.add_sized([140.0, 20.0], egui::Label::new(RichText::new(stat.filter.label()).strong()).truncate());
 
-                    painter.add(Shape::convex_polygon(points, fill_color, Stroke::new(1.0, Color32::from_gray(30))));
-                    start_angle += sweep;
-                }
-            }
+        let (rect, _) = ui.allocate_at_least(Vec2::new(260.0, 16.0), Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, Rounding::same(4.0), Color32::from_gray(40));
+        let fill_width = (rect.width() * (value as f32 / largest as f32)).max(1.0);
+        painter.rect_filled(
+            egui::Rect::from_min_size(rect.min, Vec2::new(fill_width, rect.height())),
+            Rounding::same(4.0),
+            color,
+        );
 
-            ui.add_space(20.0);
+        ui.add_space(8.0);
+        ui.label(RichText::new(format!("{:>10}", format_size(stat.bytes, DECIMAL))).monospace());
+        ui.label(RichText::new(format!("{:>5.1}%", share * 100.0)).monospace().weak());
+        ui.label(RichText::new(format!("{} files", stat.files)).weak());
+    }).response.interact(Sense::click());
 
-            // Right side: Legend
-            ui.vertical(|ui| {
-                ui.add_space(20.0);
-                for entry in &entries {
-                    ui.horizontal(|ui| {
-                        let (rect, _) = ui.allocate_at_least(Vec2::splat(12.0), egui::Sense::hover());
-                        ui.painter().rect_filled(rect, Rounding::same(2.0), entry.color);
-                        ui.label(format!("{}: {:.1}%", entry.name, entry.percentage * 100.0));
-                    });
-                    ui.add_space(4.0);
-                }
-            });
-        });
-    });
+    if response.hovered() {
+        ui.output_mut(|o| o.cursor_icon = egui::CursorIcon::PointingHand);
+    }
+    response.on_hover_text("Show only these files in the tree").clicked()
 }
@@ -1,47 +1,129 @@
 use egui::{Ui, Vec2, Color32, Rounding, Stroke, Align, Layout, RichText};
-use crate::core::disk::DiskInfo;
+use crate::core::alerts::AlertSettings;
+use crate::core::disk::{storage_devices, DiskInfo};
+use crate::core::history::UsageHistory;
 use humansize::{format_size, DECIMAL};
 
-pub fn disk_modal_ui(ctx: &egui::Context, disks: &[DiskInfo], selected_disk: &mut Option<String>, is_open: &mut bool) -> bool {
-    let mut selection_changed = false;
+const WARN_USAGE: f32 = 0.75;
+const CRITICAL_USAGE: f32 = 0.9;
+
+/// Picker state that outlives a single opening of the modal.
+pub struct DiskPicker {
+    /// Mount points ticked for a combined scan.
+    pub selected: Vec<String>,
+    pub show_all: bool,
+    /// Fullest first, so the disk most at risk is on top; otherwise mount order.
+    pub sort_by_fullness: bool,
+}
+
+impl DiskPicker {
+    pub fn new() -> Self {
+        Self {
+            selected: Vec::new(),
+            show_all: false,
+            sort_by_fullness: true,
+        }
+    }
+}
+
+pub enum DiskModalAction {
+    /// Scan the mounts in the selection.
+    Scan,
+    Refresh,
+    /// Open the usage history chart for this mount point.
+    ShowHistory(String),
+}
+
+/// Clicking a row picks that disk alone; the checkboxes build up a set of
+/// disks that are scanned together with the "Scan selected" button. Unless
+/// `show_all` is set, only real storage devices are listed.
+/// `host` names the remote machine the disks belong to, `None` for this one.
+pub fn disk_modal_ui(ctx: &egui::Context, disks: &[DiskInfo], host: Option<&str>, history: &UsageHistory, alerts: &AlertSettings, picker: &mut DiskPicker, is_open: &mut bool) -> Option<DiskModalAction> {
+    let mut action = None;
     let mut open = *is_open;
     let mut selection_made = false;
+    let title = match host {
+        Some(host) => format!("Select Storage Device on {}", host),
+        None => "Select Storage Device".to_string(),
+    };
     
-    egui::Window::new("Select Storage Device")
+    egui::Window::new(title)
+        .id(egui::Id::new("disk_modal"))
         .open(&mut open)
         .resizable(false)
         .collapsible(false)
         .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
         .fixed_size([480.0, 420.0])
         .show(ctx, |ui| {
+            let mut devices = if picker.show_all { disks.to_vec() } else { storage_devices(disks) };
+            if picker.sort_by_fullness {
+                devices.sort_by(|a, b| b.fullness().total_cmp(&a.fullness()));
+            }
+            let hidden = disks.len() - devices.len() - devices.iter().map(|d| d.also_mounted_at.len()).sum::<usize>();
             ui.add_space(10.0);
             
             egui::ScrollArea::vertical()
                 .id_source("disk_modal_scroll")
-                .max_height(360.0)
+                .max_height(330.0)
                 .show(ui, |ui| {
                     ui.vertical(|ui| {
-                        for disk in disks {
-                            if disk_row_minimal(ui, disk) {
-                                *selected_disk = Some(disk.mount_point.clone());
-                                selection_changed = true;
-                                selection_made = true;
-                            }
+                        for disk in &devices {
+                            ui.horizontal(|ui| {
+                                let mut checked = picker.selected.contains(&disk.mount_point);
+                                if ui.checkbox(&mut checked, "").changed() {
+                                    if checked {
+                                        picker.selected.push(disk.mount_point.clone());
+                                    } else {
+                                        picker.selected.retain(|m| *m != disk.mount_point);
+                                    }
+                                }
+                                match disk_row_minimal(ui, disk, alerts.is_low(disk), history.days_until_full(&disk.mount_point)) {
+                                    Some(DiskModalAction::Scan) => {
+                                        picker.selected = vec![disk.mount_point.clone()];
+                                        action = Some(DiskModalAction::Scan);
+                                        selection_made = true;
+                                    }
+                                    Some(other) => action = Some(other),
+                                    None => {}
+                                }
+                            });
                             ui.add_space(4.0);
                         }
                     });
                 });
             
             ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut picker.show_all, "Show all mounts");
+                ui.checkbox(&mut picker.sort_by_fullness, "Fullest first");
+                if !picker.show_all && hidden > 0 {
+                    ui.label(RichText::new(format!("{} virtual hidden", hidden)).small().weak());
+                }
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if ui.small_button("⟳ Refresh").on_hover_text("Re-read mounted filesystems").clicked() {
+                        action = Some(DiskModalAction::Refresh);
+                    }
+                });
+            });
+            ui.vertical_centered(|ui| {
+                let label = format!("Scan {} selected", picker.selected.len());
+                if ui.add_enabled(!picker.selected.is_empty(), egui::Button::new(label)).clicked() {
+                    action = Some(DiskModalAction::Scan);
+                    selection_made = true;
+                }
+            });
         });
     
     *is_open = open && !selection_made;
-    selection_changed
+    action
 }
 
-fn disk_row_minimal(ui: &mut Ui, disk: &DiskInfo) -> bool {
+/// A click on the row itself means "scan this disk" (`Scan`). Disks below
+/// their alert threshold get a red outline.
+fn disk_row_minimal(ui: &mut Ui, disk: &DiskInfo, is_low: bool, days_until_full: Option<f64>) -> Option<DiskModalAction> {
+    let mut action = None;
     let width = ui.available_width();
-    let height = 48.0;
+    let height = 74.0;
     
     let (rect, response) = ui.allocate_at_least(Vec2::new(width, height), egui::Sense::click());
     let is_hovered = response.hovered();
@@ -54,6 +136,9 @@ fn disk_row_minimal(ui: &mut Ui, disk: &DiskInfo) -> bool {
     };
     
     ui.painter().rect_filled(rect, Rounding::same(6.0), bg_color);
+    if is_low {
+        ui.painter().rect_stroke(rect, Rounding::same(6.0), Stroke::new(1.5, usage_color(1.0)));
+    }
     
     // Accent bar on hover
     if is_hovered {
@@ -65,8 +150,8 @@ fn disk_row_minimal(ui: &mut Ui, disk: &DiskInfo) -> bool {
     }
 
     // Content
-    ui.allocate_ui_at_rect(rect.shrink2(Vec2::new(16.0, 0.0)), |ui| {
-        ui.horizontal_centered(|ui| {
+    ui.allocate_ui_at_rect(rect.shrink2(Vec2::new(16.0, 6.0)), |ui| {
+        ui.horizontal(|ui| {
             // Left: Name and Path in one line
             let name = if disk.name.is_empty() { "Local Disk" } else { &disk.name };
             ui.label(RichText::new(name).strong().size(14.0).color(Color32::from_rgb(230, 235, 245)));
@@ -74,6 +159,9 @@ fn disk_row_minimal(ui: &mut Ui, disk: &DiskInfo) -> bool {
             ui.label(RichText::new(format!("({})", disk.mount_point)).small().color(Color32::from_rgb(110, 115, 125)));
             
             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui.small_button("📈").on_hover_text("Usage history").clicked() {
+                    action = Some(DiskModalAction::ShowHistory(disk.mount_point.clone()));
+                }
                 // Right: Storage Info in one line
                 ui.label(
                     RichText::new(format!(" / {}", format_size(disk.total_space, DECIMAL)))
@@ -89,11 +177,69 @@ fn disk_row_minimal(ui: &mut Ui, disk: &DiskInfo) -> bool {
                 ui.label(RichText::new("Free:").small().color(Color32::from_rgb(100, 105, 115)));
             });
         });
+        ui.horizontal(|ui| {
+            let usage = disk.usage();
+            if is_low {
+                ui.label(RichText::new("⚠ Low space").small().strong().color(usage_color(1.0)));
+            }
+            ui.label(RichText::new(format!("{:.0}% used", usage * 100.0)).small().color(usage_color(usage)));
+            if let Some(inodes) = disk.inode_usage() {
+                ui.label(RichText::new(format!("· inodes {:.0}%", inodes * 100.0)).small().color(usage_color(inodes)));
+            }
+            if let Some(days) = days_until_full.filter(|d| *d < 365.0) {
+                ui.label(RichText::new(format!("· full in ~{:.0} days", days)).small().color(usage_color(1.0 - days as f32 / 365.0)))
+                    .on_hover_text("Projected from the last 30 days of usage history");
+            }
+            let bar_width = ui.available_width();
+            usage_bar(ui, bar_width, usage);
+        });
+        ui.label(RichText::new(disk_details(disk)).small().color(Color32::from_rgb(110, 115, 125)));
     });
 
     if is_hovered {
         ui.output_mut(|o| o.cursor_icon = egui::CursorIcon::PointingHand);
     }
 
-    response.clicked()
+    if response.clicked() && action.is_none() {
+        action = Some(DiskModalAction::Scan);
+    }
+    action
+}
+
+/// One-line summary of filesystem, device, media and inode state.
+fn disk_details(disk: &DiskInfo) -> String {
+    let mut parts = vec![disk.file_system.clone(), disk.device.clone(), disk.kind_label().to_string()];
+    if disk.is_removable {
+        parts.push("Removable".to_string());
+    }
+    if disk.is_read_only {
+        parts.push("Read-only".to_string());
+    }
+    if let (Some(total), Some(free)) = (disk.inodes_total, disk.inodes_free) {
+        parts.push(format!("Inodes: {} free of {}", free, total));
+    }
+    if !disk.also_mounted_at.is_empty() {
+        parts.push(format!("Also at {}", disk.also_mounted_at.join(", ")));
+    }
+    parts.retain(|p| !p.is_empty());
+    parts.join(" · ")
+}
+
+fn usage_color(usage: f32) -> Color32 {
+    if usage >= CRITICAL_USAGE {
+        Color32::from_rgb(235, 90, 90)
+    } else if usage >= WARN_USAGE {
+        Color32::from_rgb(235, 180, 70)
+    } else {
+        Color32::from_rgb(90, 190, 120)
+    }
+}
+
+/// Used/free capacity bar, filled in the threshold color.
+fn usage_bar(ui: &mut Ui, width: f32, usage: f32) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(width, 6.0), egui::Sense::hover());
+    ui.painter().rect_filled(rect, Rounding::same(3.0), Color32::from_gray(50));
+    let mut used = rect;
+    used.set_width(rect.width() * usage.clamp(0.0, 1.0));
+    ui.painter().rect_filled(used, Rounding::same(3.0), usage_color(usage));
 }
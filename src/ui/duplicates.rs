@@ -0,0 +1,177 @@
+use egui::{Ui, RichText};
+use crate::core::duplicates::{DuplicateFinder, DuplicateGroup, DuplicateMessage, DuplicateProgress};
+use crate::core::scanner::FileNode;
+use crate::ui::tree::{item_context_menu, TreeAction};
+use humansize::{format_size, DECIMAL};
+use std::path::{Path, PathBuf};
+
+pub enum DuplicateAction {
+    TrashOthers { keep: PathBuf, others: Vec<PathBuf> },
+    HardLinkOthers { keep: PathBuf, others: Vec<PathBuf> },
+    Item(TreeAction),
+}
+
+/// Duplicate file finder: runs a `DuplicateFinder` and lists the groups it finds.
+pub struct DuplicatesView {
+    min_size_mb: u64,
+    finder: Option<DuplicateFinder>,
+    progress: Option<DuplicateProgress>,
+    groups: Vec<DuplicateGroup>,
+    keep: Vec<usize>,
+    has_run: bool,
+}
+
+impl DuplicatesView {
+    pub fn new() -> Self {
+        Self {
+            min_size_mb: 1,
+            finder: None,
+            progress: None,
+            groups: Vec::new(),
+            keep: Vec::new(),
+            has_run: false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.finder.is_some()
+    }
+
+    /// Drops results after a rescan, since they may point at stale paths.
+    pub fn reset(&mut self) {
+        if let Some(finder) = &self.finder {
+            finder.cancel();
+        }
+        *self = Self { min_size_mb: self.min_size_mb, ..Self::new() };
+    }
+
+    /// Forgets a group once an action has been applied to it.
+    pub fn remove_group_of(&mut self, keep: &Path) {
+        if let Some(idx) = self.groups.iter().position(|g| g.paths.iter().any(|p| p == keep)) {
+            self.groups.remove(idx);
+            self.keep.remove(idx);
+        }
+    }
+
+    pub fn poll(&mut self) {
+        let Some(finder) = &self.finder else {
+            return;
+        };
+        while let Some(msg) = finder.try_recv() {
+            match msg {
+                DuplicateMessage::Progress(p) => self.progress = Some(p),
+                DuplicateMessage::Completed(groups) => {
+                    self.keep = vec![0; groups.len()];
+                    self.groups = groups;
+                    self.finder = None;
+                    self.has_run = true;
+                    return;
+                }
+                DuplicateMessage::Cancelled => {
+                    self.finder = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, root: &FileNode) -> Option<DuplicateAction> {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading(RichText::new("Duplicate Files").strong().size(20.0));
+            ui.add_space(20.0);
+            ui.label("Minimum size:");
+            ui.add_enabled(
+                self.finder.is_none(),
+                egui::DragValue::new(&mut self.min_size_mb).range(0..=1_000_000).suffix(" MB"),
+            );
+            if let Some(finder) = &self.finder {
+                if ui.button("Cancel").clicked() {
+                    finder.cancel();
+                }
+            } else if ui.button("🔍 Find duplicates").clicked() {
+                self.groups.clear();
+                self.keep.clear();
+                self.progress = None;
+                self.finder = Some(DuplicateFinder::new(root, self.min_size_mb * 1_000_000));
+            }
+        });
+        ui.add_space(10.0);
+
+        if self.finder.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                if let Some(p) = &self.progress {
+                    ui.label(format!(
+                        "{}: {} / {} files ({})",
+                        p.stage.label(),
+                        p.files_done,
+                        p.files_total,
+                        format_size(p.bytes_hashed, DECIMAL)
+                    ));
+                } else {
+                    ui.label("Grouping files by size...");
+                }
+            });
+            if let Some(p) = &self.progress {
+                let ratio = p.files_done as f32 / p.files_total.max(1) as f32;
+                ui.add(egui::ProgressBar::new(ratio).rounding(egui::Rounding::same(6.0)));
+            }
+            return None;
+        }
+
+        if !self.has_run {
+            ui.label(RichText::new("Files are grouped by size, then compared by partial and full content hashes.").weak());
+            return None;
+        }
+        if self.groups.is_empty() {
+            ui.label("No duplicates found.");
+            return None;
+        }
+
+        let wasted: u64 = self.groups.iter().map(|g| g.wasted()).sum();
+        ui.label(RichText::new(format!("{} groups, {} reclaimable", self.groups.len(), format_size(wasted, DECIMAL))).strong());
+        ui.add_space(6.0);
+
+        let mut action = None;
+        egui::ScrollArea::vertical()
+            .id_source("duplicates_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (i, group) in self.groups.iter().enumerate() {
+                    let title = format!(
+                        "{} × {} — {} wasted",
+                        group.paths.len(),
+                        format_size(group.size, DECIMAL),
+                        format_size(group.wasted(), DECIMAL)
+                    );
+                    egui::CollapsingHeader::new(title)
+                        .id_source(("dup_group", &group.paths[0]))
+                        .show(ui, |ui| {
+                            for (j, path) in group.paths.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.radio_value(&mut self.keep[i], j, "Keep");
+                                    let response = ui.label(path.to_string_lossy());
+                                    let mut item_action = None;
+                                    item_context_menu(&response, path, &mut item_action);
+                                    if let Some(a) = item_action {
+                                        action = Some(DuplicateAction::Item(a));
+                                    }
+                                });
+                            }
+                            let keep = group.paths[self.keep[i]].clone();
+                            let others: Vec<PathBuf> = group.paths.iter().filter(|p| **p != keep).cloned().collect();
+                            ui.horizontal(|ui| {
+                                if ui.button("🗑 Keep one, trash the rest").clicked() {
+                                    action = Some(DuplicateAction::TrashOthers { keep: keep.clone(), others: others.clone() });
+                                }
+                                if ui.button("🔗 Replace with hard links").clicked() {
+                                    action = Some(DuplicateAction::HardLinkOthers { keep, others });
+                                }
+                            });
+                        });
+                }
+            });
+        action
+    }
+}
@@ -0,0 +1,82 @@
+use egui::RichText;
+use egui_plot::{HLine, Line, Plot, PlotPoints};
+use crate::core::history::UsageHistory;
+use chrono::{Local, TimeZone};
+use humansize::{format_size, DECIMAL};
+
+const SECS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// Used space over time for one mount, with its capacity as a reference line.
+/// Clears `mount_point` when closed.
+pub fn usage_history_window(ctx: &egui::Context, history: &UsageHistory, mount_point: &mut Option<String>) {
+    let Some(mount) = mount_point.clone() else {
+        return;
+    };
+    let mut open = true;
+
+    egui::Window::new(format!("Usage history — {}", mount))
+        .open(&mut open)
+        .collapsible(false)
+        .default_size([560.0, 340.0])
+        .show(ctx, |ui| {
+            let samples = history.samples(&mount);
+            if samples.len() < 2 {
+                ui.label(RichText::new("Not enough history yet. Gate records usage each time it runs.").weak());
+                return;
+            }
+
+            let first = samples[0];
+            let last = samples[samples.len() - 1];
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} samples since {}",
+                    samples.len(),
+                    format_day(first.time as f64 / SECS_PER_DAY)
+                ));
+                ui.separator();
+                match history.growth_per_day(&mount) {
+                    Some(growth) => {
+                        let sign = if growth < 0.0 { "-" } else { "+" };
+                        ui.label(format!("{}{}/day", sign, format_size(growth.abs() as u64, DECIMAL)));
+                    }
+                    None => {
+                        ui.label(RichText::new("Trend needs more than a day of history").weak());
+                    }
+                }
+                if let Some(days) = history.days_until_full(&mount) {
+                    ui.separator();
+                    ui.label(RichText::new(format!("Full in ~{:.0} days", days)).strong());
+                }
+            });
+            ui.add_space(6.0);
+
+            let points: PlotPoints = samples.iter()
+                .map(|s| [s.time as f64 / SECS_PER_DAY, s.used as f64])
+                .collect();
+            Plot::new("usage_history_plot")
+                .allow_scroll(false)
+                .x_axis_formatter(|mark, _| format_day(mark.value))
+                .y_axis_formatter(|mark, _| format_size(mark.value.max(0.0) as u64, DECIMAL))
+                .label_formatter(|_, point| {
+                    format!("{}\n{}", format_day(point.x), format_size(point.y.max(0.0) as u64, DECIMAL))
+                })
+                .include_y(0.0)
+                .include_y(last.total as f64)
+                .show(ui, |plot| {
+                    plot.hline(HLine::new(last.total as f64).name("Capacity"));
+                    plot.line(Line::new(points).name("Used"));
+                });
+        });
+
+    if !open {
+        *mount_point = None;
+    }
+}
+
+/// Formats an x-axis value in days since the Unix epoch as a local date.
+fn format_day(days: f64) -> String {
+    Local.timestamp_opt((days * SECS_PER_DAY) as i64, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,163 @@
+use egui::{Ui, RichText};
+use std::path::{Path, PathBuf};
+
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Typed path field with directory autocomplete. Tab accepts the first
+/// suggestion; Enter or the Scan button opens the path.
+pub struct LocationInput {
+    pub text: String,
+    suggestions: Vec<PathBuf>,
+    suggestions_for: Option<String>,
+    popup_hovered: bool,
+}
+
+impl LocationInput {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            suggestions: Vec::new(),
+            suggestions_for: None,
+            popup_hovered: false,
+        }
+    }
+
+    fn refresh_suggestions(&mut self) {
+        if self.suggestions_for.as_deref() == Some(self.text.as_str()) {
+            return;
+        }
+        self.suggestions_for = Some(self.text.clone());
+        self.suggestions = directory_suggestions(&self.text);
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, width: f32) -> Option<PathBuf> {
+        let mut open = None;
+        let edit_id = ui.make_persistent_id("location_input");
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - width) / 2.0);
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.text)
+                    .id(edit_id)
+                    .hint_text("Type a folder path, e.g. ~/projects")
+                    .desired_width(width - 80.0),
+            );
+            if response.has_focus() {
+                self.refresh_suggestions();
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                    if let Some(first) = self.suggestions.first() {
+                        self.text = with_trailing_separator(first);
+                        set_cursor_to_end(ui, edit_id, &self.text);
+                    }
+                }
+            }
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.add_sized([72.0, 20.0], egui::Button::new("Scan")).clicked() || submitted) && !self.text.trim().is_empty() {
+                open = Some(expand_home(self.text.trim()));
+            }
+
+            // Keep the popup while the pointer is over it, otherwise a click on a
+            // suggestion would first defocus the field and hide the popup.
+            let show_popup = response.has_focus() || self.popup_hovered;
+            self.popup_hovered = false;
+            if show_popup && !self.suggestions.is_empty() {
+                let area = egui::Area::new(edit_id.with("suggestions"))
+                    .order(egui::Order::Foreground)
+                    .fixed_pos(response.rect.left_bottom())
+                    .show(ui.ctx(), |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.set_min_width(response.rect.width());
+                            let mut picked = None;
+                            for suggestion in &self.suggestions {
+                                if ui.selectable_label(false, suggestion.to_string_lossy()).clicked() {
+                                    picked = Some(with_trailing_separator(suggestion));
+                                }
+                            }
+                            if let Some(text) = picked {
+                                self.text = text;
+                                set_cursor_to_end(ui, edit_id, &self.text);
+                                ui.memory_mut(|m| m.request_focus(edit_id));
+                            }
+                        });
+                    });
+                self.popup_hovered = area.response.contains_pointer();
+            }
+        });
+
+        open
+    }
+}
+
+/// Clickable list of recently scanned locations.
+pub fn recent_locations_ui(ui: &mut Ui, recent: &[String]) -> Option<PathBuf> {
+    if recent.is_empty() {
+        return None;
+    }
+    let mut open = None;
+    ui.label(RichText::new("Recent").weak().small());
+    ui.add_space(4.0);
+    for location in recent {
+        let missing = !Path::new(location).is_dir();
+        let text = RichText::new(location).monospace();
+        let response = ui.add_enabled(!missing, egui::Button::new(text).frame(false));
+        if response.clicked() {
+            open = Some(PathBuf::from(location));
+        }
+    }
+    open
+}
+
+fn set_cursor_to_end(ui: &Ui, id: egui::Id, text: &str) {
+    if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+        let cursor = egui::text::CCursor::new(text.chars().count());
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(cursor)));
+        state.store(ui.ctx(), id);
+    }
+}
+
+fn with_trailing_separator(path: &Path) -> String {
+    let mut s = path.to_string_lossy().to_string();
+    if !s.ends_with(std::path::MAIN_SEPARATOR) {
+        s.push(std::path::MAIN_SEPARATOR);
+    }
+    s
+}
+
+pub fn expand_home(input: &str) -> PathBuf {
+    match input.strip_prefix('~') {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches(['/', '\\'])),
+            None => PathBuf::from(input),
+        },
+        None => PathBuf::from(input),
+    }
+}
+
+/// Subdirectories of the typed parent whose names start with the typed remainder.
+fn directory_suggestions(input: &str) -> Vec<PathBuf> {
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let expanded = expand_home(input);
+    let ends_with_sep = input.ends_with(['/', '\\']);
+    let (parent, prefix) = if ends_with_sep {
+        (expanded.as_path(), String::new())
+    } else {
+        match (expanded.parent(), expanded.file_name()) {
+            (Some(parent), Some(name)) => (parent, name.to_string_lossy().to_lowercase()),
+            _ => return Vec::new(),
+        }
+    };
+    let Ok(entries) = std::fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| e.file_name().to_string_lossy().to_lowercase().starts_with(&prefix))
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    dirs.truncate(MAX_SUGGESTIONS);
+    dirs
+}
@@ -1,2 +1,12 @@
 pub mod tree;
 pub mod disk_select;
+pub mod transfer;
+pub mod top_files;
+pub mod charts;
+pub mod age;
+pub mod duplicates;
+pub mod location;
+pub mod history;
+pub mod alerts;
+pub mod reclaim;
+pub mod remote;
@@ -0,0 +1,328 @@
+use egui::{Ui, RichText};
+use crate::core::age::{age_days, format_time};
+use crate::core::artifacts::{find_artifacts, ArtifactProject};
+use crate::core::cleanup::{find_cleanup_suggestions, CleanupSuggestion};
+use crate::core::rules::{distinct_paths, write_example, RuleMatch, RuleSet};
+use crate::core::scanner::FileNode;
+use crate::ui::tree::{item_context_menu, TreeAction};
+use humansize::{format_size, DECIMAL};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+pub enum ReclaimAction {
+    /// Move the selected paths to the trash.
+    Trash(Vec<PathBuf>),
+    /// Open this machine's rules file in the default editor, whatever scan is shown.
+    EditRules(PathBuf),
+    Item(TreeAction),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReclaimSection {
+    Artifacts,
+    Caches,
+    Rules,
+}
+
+/// "Reclaimable" tab: regenerable developer artifacts grouped by project,
+/// well-known system caches and logs, and matches of the user's own rules.
+pub struct ReclaimView {
+    section: ReclaimSection,
+    projects: Vec<ArtifactProject>,
+    suggestions: Vec<CleanupSuggestion>,
+    selected: HashSet<PathBuf>,
+    /// Locations of the checked cache suggestions.
+    selected_caches: HashSet<PathBuf>,
+    rule_matches: Vec<RuleMatch>,
+    /// Names of the checked rules.
+    selected_rules: HashSet<String>,
+    rules_error: Option<String>,
+    idle_days: u64,
+    built_for: Option<(PathBuf, u64)>,
+}
+
+impl ReclaimView {
+    pub fn new() -> Self {
+        Self {
+            section: ReclaimSection::Artifacts,
+            projects: Vec::new(),
+            suggestions: Vec::new(),
+            selected: HashSet::new(),
+            selected_caches: HashSet::new(),
+            rule_matches: Vec::new(),
+            selected_rules: HashSet::new(),
+            rules_error: None,
+            idle_days: 30,
+            built_for: None,
+        }
+    }
+
+    fn selected_size(&self) -> u64 {
+        self.projects.iter()
+            .flat_map(|p| &p.artifacts)
+            .filter(|a| self.selected.contains(&a.path))
+            .map(|a| a.size)
+            .sum()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, root: &FileNode) -> Option<ReclaimAction> {
+        let key = (root.path.clone(), root.size);
+        if self.built_for.as_ref() != Some(&key) {
+            self.projects = find_artifacts(root);
+            let found: HashSet<&PathBuf> = self.projects.iter().flat_map(|p| &p.artifacts).map(|a| &a.path).collect();
+            self.selected.retain(|p| found.contains(p));
+            self.suggestions = find_cleanup_suggestions(root);
+            let locations: HashSet<&PathBuf> = self.suggestions.iter().map(|s| &s.location).collect();
+            self.selected_caches.retain(|p| locations.contains(p));
+            self.evaluate_rules(root);
+            self.built_for = Some(key);
+        }
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.heading(RichText::new("Reclaimable").strong().size(20.0));
+            ui.add_space(20.0);
+            let artifacts: u64 = self.projects.iter().map(|p| p.size()).sum();
+            let caches: u64 = self.suggestions.iter().map(|s| s.size).sum();
+            ui.selectable_value(&mut self.section, ReclaimSection::Artifacts, format!("Developer artifacts ({})", format_size(artifacts, DECIMAL)));
+            ui.selectable_value(&mut self.section, ReclaimSection::Caches, format!("Caches & logs ({})", format_size(caches, DECIMAL)));
+            let rules: u64 = self.rule_matches.iter().map(|m| m.size).sum();
+            ui.selectable_value(&mut self.section, ReclaimSection::Rules, format!("Custom rules ({})", format_size(rules, DECIMAL)));
+        });
+        ui.add_space(6.0);
+
+        match self.section {
+            ReclaimSection::Artifacts => self.artifacts_ui(ui),
+            ReclaimSection::Caches => self.caches_ui(ui),
+            ReclaimSection::Rules => self.rules_ui(ui, root),
+        }
+    }
+
+    fn evaluate_rules(&mut self, root: &FileNode) {
+        let result = RuleSet::load_default().and_then(|set| set.evaluate(root, SystemTime::now()));
+        match result {
+            Ok(matches) => {
+                self.rule_matches = matches;
+                self.rules_error = None;
+            }
+            Err(e) => {
+                self.rule_matches.clear();
+                self.rules_error = Some(format!("{:#}", e));
+            }
+        }
+        let names: HashSet<&String> = self.rule_matches.iter().map(|m| &m.rule.name).collect();
+        self.selected_rules.retain(|n| names.contains(n));
+    }
+
+    fn artifacts_ui(&mut self, ui: &mut Ui) -> Option<ReclaimAction> {
+        let mut action = None;
+        let now = SystemTime::now();
+        ui.horizontal(|ui| {
+            ui.label("Projects untouched for");
+            ui.add(egui::DragValue::new(&mut self.idle_days).range(0..=3650).suffix(" days"));
+            if ui.button("Select idle").clicked() {
+                for project in &self.projects {
+                    if age_days(project.last_modified, now).is_some_and(|d| d >= self.idle_days) {
+                        self.selected.extend(project.artifacts.iter().map(|a| a.path.clone()));
+                    }
+                }
+            }
+            if ui.button("Select all").clicked() {
+                self.selected = self.projects.iter().flat_map(|p| &p.artifacts).map(|a| a.path.clone()).collect();
+            }
+            if ui.button("Clear").clicked() {
+                self.selected.clear();
+            }
+            ui.separator();
+            let label = format!("🗑 Trash {} selected ({})", self.selected.len(), format_size(self.selected_size(), DECIMAL));
+            if ui.add_enabled(!self.selected.is_empty(), egui::Button::new(label)).clicked() {
+                action = Some(ReclaimAction::Trash(self.selected.drain().collect()));
+            }
+        });
+        ui.add_space(6.0);
+
+        if self.projects.is_empty() {
+            ui.label(RichText::new("No build output found. Artifacts are recognised by their project files, e.g. target/ next to Cargo.toml.").weak());
+            return action;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("reclaim_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for project in &self.projects {
+                    let idle = age_days(project.last_modified, now)
+                        .map_or(String::new(), |d| format!(" — last modified {} ({} days ago)", format_time(project.last_modified), d));
+                    let title = format!("{} — {}{}", project.root.display(), format_size(project.size(), DECIMAL), idle);
+                    egui::CollapsingHeader::new(title)
+                        .id_source(("artifact_project", &project.root))
+                        .show(ui, |ui| {
+                            for artifact in &project.artifacts {
+                                ui.horizontal(|ui| {
+                                    let mut checked = self.selected.contains(&artifact.path);
+                                    if ui.checkbox(&mut checked, "").changed() {
+                                        if checked {
+                                            self.selected.insert(artifact.path.clone());
+                                        } else {
+                                            self.selected.remove(&artifact.path);
+                                        }
+                                    }
+                                    ui.label(RichText::new(artifact.kind.label()).strong());
+                                    ui.label(format_size(artifact.size, DECIMAL));
+                                    let response = ui.label(RichText::new(artifact.path.to_string_lossy()).weak())
+                                        .on_hover_text(artifact.kind.regenerate_hint());
+                                    let mut item_action = None;
+                                    item_context_menu(&response, &artifact.path, &mut item_action);
+                                    if let Some(a) = item_action {
+                                        action = Some(ReclaimAction::Item(a));
+                                    }
+                                });
+                            }
+                        });
+                }
+            });
+        action
+    }
+
+    fn caches_ui(&mut self, ui: &mut Ui) -> Option<ReclaimAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            let selected: Vec<&CleanupSuggestion> = self.suggestions.iter()
+                .filter(|s| self.selected_caches.contains(&s.location))
+                .collect();
+            let size: u64 = selected.iter().map(|s| s.size).sum();
+            let label = format!("🗑 Trash {} selected ({})", selected.len(), format_size(size, DECIMAL));
+            if ui.add_enabled(!selected.is_empty(), egui::Button::new(label)).clicked() {
+                action = Some(ReclaimAction::Trash(selected.iter().flat_map(|s| s.paths.iter().cloned()).collect()));
+            }
+            ui.label(RichText::new("System locations may need administrator rights.").small().weak());
+        });
+        if action.is_some() {
+            self.selected_caches.clear();
+        }
+        ui.add_space(6.0);
+
+        if self.suggestions.is_empty() {
+            ui.label(RichText::new("No known caches or old logs in the scanned locations.").weak());
+            return action;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("cleanup_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for suggestion in &self.suggestions {
+                    ui.horizontal(|ui| {
+                        let mut checked = self.selected_caches.contains(&suggestion.location);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                self.selected_caches.insert(suggestion.location.clone());
+                            } else {
+                                self.selected_caches.remove(&suggestion.location);
+                            }
+                        }
+                        ui.label(RichText::new(suggestion.label).strong());
+                        ui.label(format_size(suggestion.size, DECIMAL));
+                        let detail = if suggestion.paths.len() == 1 && suggestion.paths[0] == suggestion.location {
+                            suggestion.location.to_string_lossy().to_string()
+                        } else {
+                            format!("{} ({} items)", suggestion.location.display(), suggestion.paths.len())
+                        };
+                        let response = ui.label(RichText::new(detail).weak());
+                        let mut item_action = None;
+                        item_context_menu(&response, &suggestion.location, &mut item_action);
+                        if let Some(a) = item_action {
+                            action = Some(ReclaimAction::Item(a));
+                        }
+                    });
+                    ui.label(RichText::new(suggestion.description).small().weak());
+                    ui.add_space(6.0);
+                }
+            });
+        action
+    }
+
+    fn rules_ui(&mut self, ui: &mut Ui, root: &FileNode) -> Option<ReclaimAction> {
+        let mut action = None;
+        let rules_path = RuleSet::default_path();
+        ui.horizontal(|ui| {
+            if let Some(path) = &rules_path {
+                ui.label(RichText::new(path.to_string_lossy()).monospace().weak());
+                if path.exists() {
+                    if ui.button("Edit").clicked() {
+                        action = Some(ReclaimAction::EditRules(path.clone()));
+                    }
+                } else if ui.button("Create example").clicked() {
+                    if let Err(e) = write_example(path) {
+                        self.rules_error = Some(format!("{:#}", e));
+                    } else {
+                        action = Some(ReclaimAction::EditRules(path.clone()));
+                    }
+                }
+            }
+            if ui.button("⟳ Reload").clicked() {
+                self.evaluate_rules(root);
+            }
+            ui.separator();
+            let selected: Vec<&RuleMatch> = self.rule_matches.iter()
+                .filter(|m| self.selected_rules.contains(&m.rule.name))
+                .collect();
+            let size: u64 = selected.iter().map(|m| m.size).sum();
+            let label = format!("🗑 Trash {} selected ({})", selected.len(), format_size(size, DECIMAL));
+            if ui.add_enabled(!selected.is_empty(), egui::Button::new(label)).clicked() {
+                action = Some(ReclaimAction::Trash(distinct_paths(selected)));
+                self.selected_rules.clear();
+            }
+        });
+        ui.add_space(6.0);
+
+        if let Some(err) = &self.rules_error {
+            ui.colored_label(egui::Color32::from_rgb(235, 90, 90), err);
+            return action;
+        }
+        if self.rule_matches.is_empty() {
+            ui.label(RichText::new("No rule matched anything in the scanned locations.").weak());
+            return action;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("rules_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for rule_match in &self.rule_matches {
+                    ui.horizontal(|ui| {
+                        let name = &rule_match.rule.name;
+                        let mut checked = self.selected_rules.contains(name);
+                        if ui.checkbox(&mut checked, "").changed() {
+                            if checked {
+                                self.selected_rules.insert(name.clone());
+                            } else {
+                                self.selected_rules.remove(name);
+                            }
+                        }
+                        ui.label(RichText::new(name).strong());
+                        ui.label(format_size(rule_match.size, DECIMAL));
+                        ui.label(RichText::new(&rule_match.rule.query).monospace().weak());
+                    });
+                    if !rule_match.rule.description.is_empty() {
+                        ui.label(RichText::new(&rule_match.rule.description).small().weak());
+                    }
+                    egui::CollapsingHeader::new(format!("{} matches", rule_match.paths.len()))
+                        .id_source(("rule_matches", &rule_match.rule.name))
+                        .show(ui, |ui| {
+                            for path in &rule_match.paths {
+                                let response = ui.label(path.to_string_lossy());
+                                let mut item_action = None;
+                                item_context_menu(&response, path, &mut item_action);
+                                if let Some(a) = item_action {
+                                    action = Some(ReclaimAction::Item(a));
+                                }
+                            }
+                        });
+                    ui.add_space(6.0);
+                }
+            });
+        action
+    }
+}
@@ -0,0 +1,103 @@
+use egui::RichText;
+use crate::core::disk::DiskInfo;
+use crate::core::remote::{RemoteAgent, Transport};
+use crate::core::settings::RemoteSettings;
+use crate::ui::disk_select::DiskPicker;
+use std::time::Instant;
+
+/// A connected host: its disks, as last reported by the agent, and the
+/// picker state for choosing among them.
+pub struct RemoteSession {
+    pub agent: RemoteAgent,
+    pub disks: Vec<DiskInfo>,
+    pub picker: DiskPicker,
+    pub disks_requested_at: Instant,
+}
+
+impl RemoteSession {
+    pub fn new(mut agent: RemoteAgent) -> Self {
+        agent.request_disks();
+        Self {
+            agent,
+            disks: Vec::new(),
+            picker: DiskPicker::new(),
+            disks_requested_at: Instant::now(),
+        }
+    }
+
+    pub fn host(&self) -> String {
+        self.agent.transport.label()
+    }
+
+    pub fn refresh_disks(&mut self) {
+        self.agent.request_disks();
+        self.disks_requested_at = Instant::now();
+    }
+
+    /// Takes a new disk list, dropping selected mounts that went away.
+    pub fn set_disks(&mut self, disks: Vec<DiskInfo>) {
+        self.picker.selected.retain(|m| disks.iter().any(|d| d.mount_point == *m));
+        self.disks = disks;
+    }
+}
+
+/// Asks for the host to connect to. Gate must be installed there; it is
+/// started as `COMMAND agent` through the system `ssh`.
+pub struct ConnectForm {
+    pub host: String,
+}
+
+impl ConnectForm {
+    pub fn new() -> Self {
+        Self { host: String::new() }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, settings: &mut RemoteSettings, is_open: &mut bool) -> Option<Transport> {
+        let mut transport = None;
+        egui::Window::new("Scan a Remote Host")
+            .open(is_open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new("Gate runs on the host over SSH using your keys and ~/.ssh/config.").weak());
+                ui.add_space(8.0);
+                let mut submitted = false;
+                egui::Grid::new("remote_form").num_columns(2).spacing([10.0, 8.0]).show(ui, |ui| {
+                    ui.label("Host");
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.host).hint_text("user@server").desired_width(260.0));
+                    submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    ui.end_row();
+                    ui.label("Gate command");
+                    ui.add(egui::TextEdit::singleline(&mut settings.command).desired_width(260.0))
+                        .on_hover_text("How Gate is started on the host, e.g. ~/bin/gate");
+                    ui.end_row();
+                });
+
+                if !settings.recent_hosts.is_empty() {
+                    ui.add_space(8.0);
+                    ui.label(RichText::new("Recent hosts").small().weak());
+                    ui.horizontal_wrapped(|ui| {
+                        for host in &settings.recent_hosts {
+                            if ui.button(host).clicked() {
+                                self.host = host.clone();
+                                submitted = true;
+                            }
+                        }
+                    });
+                }
+
+                ui.add_space(10.0);
+                let host = self.host.trim();
+                // A leading dash would make ssh read the host as an option.
+                let ready = !host.is_empty() && !host.starts_with('-') && !settings.command.trim().is_empty();
+                if (ui.add_enabled(ready, egui::Button::new("Connect")).clicked() || submitted) && ready {
+                    transport = Some(Transport::Ssh {
+                        host: host.to_string(),
+                        command: settings.command.trim().to_string(),
+                    });
+                }
+            });
+        transport
+    }
+}
@@ -0,0 +1,171 @@
+use egui::{Ui, RichText};
+use egui_extras::{Column, TableBuilder};
+use crate::core::age::format_time;
+use crate::core::scanner::FileNode;
+use crate::core::settings::{FileColumn, TopFilesLayout};
+use crate::ui::tree::{item_context_menu, TreeAction};
+use humansize::{format_size, DECIMAL};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+struct FileEntry {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+    kind: String,
+}
+
+/// Flat, sortable table of the largest files under the current root.
+pub struct TopFilesView {
+    pub limit: usize,
+    sort_column: FileColumn,
+    ascending: bool,
+    entries: Vec<FileEntry>,
+    built_for: Option<(PathBuf, u64, usize)>,
+}
+
+impl TopFilesView {
+    pub fn new(layout: &TopFilesLayout) -> Self {
+        Self {
+            limit: layout.limit,
+            sort_column: layout.sort_column,
+            ascending: layout.ascending,
+            entries: Vec::new(),
+            built_for: None,
+        }
+    }
+
+    pub fn layout(&self) -> TopFilesLayout {
+        TopFilesLayout {
+            limit: self.limit,
+            sort_column: self.sort_column,
+            ascending: self.ascending,
+        }
+    }
+
+    fn rebuild(&mut self, root: &FileNode) {
+        let mut files: Vec<&FileNode> = Vec::new();
+        root.visit_files(&mut |f| files.push(f));
+        files.sort_unstable_by_key(|f| std::cmp::Reverse(f.size));
+        files.truncate(self.limit);
+
+        self.entries = files.into_iter()
+            .map(|f| FileEntry {
+                path: f.path.clone(),
+                size: f.size,
+                modified: f.modified,
+                kind: f.extension().map(|e| e.to_uppercase()).unwrap_or_else(|| "File".to_string()),
+            })
+            .collect();
+        self.sort();
+        self.built_for = Some((root.path.clone(), root.size, self.limit));
+    }
+
+    fn sort(&mut self) {
+        let column = self.sort_column;
+        let ascending = self.ascending;
+        self.entries.sort_by(|a, b| {
+            let ord = match column {
+                FileColumn::Path => a.path.cmp(&b.path),
+                FileColumn::Size => a.size.cmp(&b.size),
+                FileColumn::Modified => a.modified.cmp(&b.modified),
+                FileColumn::Type => a.kind.cmp(&b.kind).then(b.size.cmp(&a.size)),
+            };
+            if ascending { ord } else { ord.reverse() }
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, root: &FileNode, selected_path: &mut Option<PathBuf>) -> Option<TreeAction> {
+        let key = (root.path.clone(), root.size, self.limit);
+        if self.built_for.as_ref() != Some(&key) {
+            self.rebuild(root);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Show top");
+            egui::ComboBox::from_id_source("top_files_limit")
+                .selected_text(self.limit.to_string())
+                .show_ui(ui, |ui| {
+                    for n in [100, 500, 1000, 5000, 10000] {
+                        ui.selectable_value(&mut self.limit, n, n.to_string());
+                    }
+                });
+            ui.label(RichText::new(format!("{} files", self.entries.len())).weak());
+        });
+        ui.add_space(6.0);
+
+        let mut action = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 6.0;
+        let mut header_click = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::remainder().at_least(200.0).clip(true))
+            .column(Column::auto().at_least(90.0))
+            .column(Column::auto().at_least(140.0))
+            .column(Column::auto().at_least(60.0))
+            .header(row_height + 4.0, |mut header| {
+                for (label, column) in [
+                    ("Path", FileColumn::Path),
+                    ("Size", FileColumn::Size),
+                    ("Modified", FileColumn::Modified),
+                    ("Type", FileColumn::Type),
+                ] {
+                    header.col(|ui| {
+                        if self.header_clicked(ui, label, column) {
+                            header_click = Some(column);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, self.entries.len(), |mut row| {
+                    let entry = &self.entries[row.index()];
+                    let is_selected = selected_path.as_ref() == Some(&entry.path);
+                    row.col(|ui| {
+                        let response = ui.selectable_label(is_selected, entry.path.to_string_lossy());
+                        item_context_menu(&response, &entry.path, &mut action);
+                        if response.clicked() {
+                            *selected_path = Some(entry.path.clone());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(format_size(entry.size, DECIMAL)).monospace());
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(format_time(entry.modified)).monospace().weak());
+                    });
+                    row.col(|ui| {
+                        ui.label(&entry.kind);
+                    });
+                });
+            });
+
+        if let Some(column) = header_click {
+            self.toggle_sort(column);
+        }
+
+        action
+    }
+
+    fn header_clicked(&self, ui: &mut Ui, label: &str, column: FileColumn) -> bool {
+        let arrow = match (self.sort_column == column, self.ascending) {
+            (true, true) => " ⏶",
+            (true, false) => " ⏷",
+            _ => "",
+        };
+        ui.button(RichText::new(format!("{}{}", label, arrow)).strong()).clicked()
+    }
+
+    fn toggle_sort(&mut self, column: FileColumn) {
+        if self.sort_column == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.sort_column = column;
+            self.ascending = matches!(column, FileColumn::Path | FileColumn::Type);
+        }
+        self.sort();
+    }
+}
@@ -0,0 +1,105 @@
+use egui::{Color32, RichText};
+use crate::core::disk::DiskInfo;
+use crate::core::transfer::{TransferKind, TransferProgress};
+use humansize::{format_size, DECIMAL};
+use std::path::Path;
+
+pub enum MoveDialogAction {
+    Start { mount_point: String, kind: TransferKind },
+    Cancel,
+}
+
+/// "Move to…" dialog: pick a destination disk for `source`.
+/// Disks without enough free space are listed but disabled.
+pub fn move_modal_ui(
+    ctx: &egui::Context,
+    source: &Path,
+    source_size: u64,
+    disks: &[DiskInfo],
+    source_mount: Option<&str>,
+    kind: &mut TransferKind,
+) -> Option<MoveDialogAction> {
+    let mut action = None;
+    let mut open = true;
+
+    egui::Window::new("Move to…")
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .fixed_size([480.0, 420.0])
+        .show(ctx, |ui| {
+            ui.add_space(6.0);
+            ui.label(RichText::new(source.to_string_lossy()).strong());
+            ui.label(RichText::new(format_size(source_size, DECIMAL)).weak());
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Mode:");
+                ui.selectable_value(kind, TransferKind::Move, TransferKind::Move.label());
+                ui.selectable_value(kind, TransferKind::Archive, TransferKind::Archive.label());
+            });
+            ui.add_space(8.0);
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_source("move_modal_scroll")
+                .max_height(280.0)
+                .show(ui, |ui| {
+                    for disk in disks.iter().filter(|d| Some(d.mount_point.as_str()) != source_mount) {
+                        let fits = disk.available_space >= source_size;
+                        let name = if disk.name.is_empty() { "Local Disk" } else { &disk.name };
+                        ui.horizontal(|ui| {
+                            let btn = ui.add_enabled(fits, egui::Button::new(format!("{} ({})", name, disk.mount_point)));
+                            if btn.clicked() {
+                                action = Some(MoveDialogAction::Start {
+                                    mount_point: disk.mount_point.clone(),
+                                    kind: *kind,
+                                });
+                            }
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                let color = if fits { Color32::from_rgb(180, 185, 195) } else { Color32::from_rgb(255, 100, 100) };
+                                ui.label(RichText::new(format!("Free: {}", format_size(disk.available_space, DECIMAL))).color(color));
+                            });
+                        });
+                        ui.add_space(4.0);
+                    }
+                });
+        });
+
+    if !open {
+        action = Some(MoveDialogAction::Cancel);
+    }
+    action
+}
+
+/// Progress window shown while a transfer is running.
+pub fn transfer_progress_ui(ctx: &egui::Context, source: &Path, kind: TransferKind, progress: &TransferProgress) {
+    egui::Window::new(kind.label())
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .fixed_size([480.0, 160.0])
+        .show(ctx, |ui| {
+            ui.label(RichText::new(source.to_string_lossy()).strong());
+            ui.add_space(8.0);
+
+            let elapsed = progress.start_time.elapsed().as_secs_f32().max(0.001);
+            let bytes_per_sec = progress.bytes_done as f32 / elapsed;
+            ui.label(RichText::new(format!(
+                "{} / {} ({}/s)",
+                format_size(progress.bytes_done, DECIMAL),
+                format_size(progress.bytes_total, DECIMAL),
+                format_size(bytes_per_sec as u64, DECIMAL),
+            )).monospace());
+            ui.add_space(6.0);
+
+            let ratio = progress.ratio();
+            ui.add(egui::ProgressBar::new(ratio)
+                .animate(true)
+                .rounding(egui::Rounding::same(6.0))
+                .text(format!("{:.1}%", ratio * 100.0)));
+            ui.add_space(6.0);
+            ui.add(egui::Label::new(RichText::new(&progress.current_path).small().weak()).truncate());
+        });
+}
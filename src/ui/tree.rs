@@ -1,30 +1,516 @@
-use egui::{Ui, Color32, Rounding, Vec2, Align, Layout, FontId};
-use crate::core::scanner::FileNode;
+use egui::{Ui, Color32, Rounding, Vec2, Align, Layout, FontId, Key, Modifiers, RichText};
+use egui::collapsing_header::CollapsingState;
+use crate::core::age::format_time;
+use crate::core::filetypes::TypeFilter;
+use crate::core::scanner::{user_name, FileNode};
+use crate::core::search::{Query, SearchResult};
+use crate::core::settings::{Measure, TreeColumn, TreeLayout};
+use egui_extras::{Column, TableBuilder};
 use humansize::{format_size, DECIMAL};
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 #[derive(Debug, Clone)]
 pub enum TreeAction {
     Delete(PathBuf),
     Open(PathBuf),
+    MoveTo(PathBuf),
+}
+
+/// Context menu shared by every view that lists scanned items.
+pub fn item_context_menu(response: &egui::Response, path: &Path, action: &mut Option<TreeAction>) {
+    response.context_menu(|ui| {
+        if ui.button("Open").clicked() {
+            *action = Some(TreeAction::Open(path.to_path_buf()));
+            ui.close_menu();
+        }
+        if ui.button("Move to…").clicked() {
+            *action = Some(TreeAction::MoveTo(path.to_path_buf()));
+            ui.close_menu();
+        }
+        if ui.button("Delete").clicked() {
+            *action = Some(TreeAction::Delete(path.to_path_buf()));
+            ui.close_menu();
+        }
+    });
+}
+
+/// Seconds after the last keystroke before type-ahead starts a new name.
+const TYPE_AHEAD_TIMEOUT: f64 = 1.0;
+
+const KEYBOARD_HELP: &str = "↑ ↓ Home End  move\n\
+    → ←  expand / collapse, or go to parent\n\
+    Enter  show only this folder, Backspace  go up\n\
+    Delete  move to trash\n\
+    /  search, or type a name to jump to it";
+
+const COLUMNS: [(&str, TreeColumn); 7] = [
+    ("Name", TreeColumn::Name),
+    ("Size", TreeColumn::Size),
+    ("Allocated", TreeColumn::Allocated),
+    ("Items", TreeColumn::Items),
+    ("% of parent", TreeColumn::Percent),
+    ("Modified", TreeColumn::Modified),
+    ("Owner", TreeColumn::Owner),
+];
+
+/// Id of the search field in the top bar, focused by `/`.
+pub fn search_field_id() -> egui::Id {
+    egui::Id::new("tree_search")
+}
+
+/// A row of the tree as currently drawn. `indices` lead from the shown
+/// root through `children`, so drawing a row needs no search by path.
+struct Row {
+    path: PathBuf,
+    indices: Vec<usize>,
+    depth: usize,
+    /// The parent's total in the current measure, for the percentage bar.
+    parent_total: u64,
 }
 
 pub struct TreeView {
     pub selected_path: Option<PathBuf>,
+    /// Folder shown as the top of the tree, entered with Enter like in ncdu.
+    pub focus: Option<PathBuf>,
     pub search_query: String,
+    pub type_filter: Option<TypeFilter>,
+    pub flatten_results: bool,
+    pub search_error: Option<String>,
+    /// Show owners by login name. Off for trees from other machines, whose
+    /// user ids mean nothing here.
+    pub resolve_owners: bool,
+    sort_column: TreeColumn,
+    ascending: bool,
+    measure: Measure,
+    search_key: Option<(String, Option<TypeFilter>, PathBuf, u64)>,
+    search_result: Option<SearchResult>,
+    /// Visible rows, rebuilt when the tree, focus, folding, sorting or
+    /// search changes rather than every frame.
+    rows: Vec<Row>,
+    rows_key: Option<(PathBuf, u64, u64, Option<PathBuf>)>,
+    rows_stale: bool,
+    /// Login names by user id, looked up as rows show or sort by them.
+    owner_names: RefCell<HashMap<u32, String>>,
+    type_ahead: String,
+    type_ahead_at: f64,
+    scroll_to_selected: bool,
+    /// Item waiting for delete confirmation, and the row to select after.
+    pending_delete: Option<(PathBuf, Option<PathBuf>)>,
 }
 
 impl TreeView {
-    pub fn new() -> Self {
-        Self { 
+    pub fn new(layout: &TreeLayout) -> Self {
+        Self {
             selected_path: None,
+            focus: None,
             search_query: String::new(),
+            type_filter: None,
+            flatten_results: false,
+            search_error: None,
+            resolve_owners: true,
+            sort_column: layout.sort_column,
+            ascending: layout.ascending,
+            measure: Measure::Bytes,
+            search_key: None,
+            search_result: None,
+            rows: Vec::new(),
+            rows_key: None,
+            rows_stale: true,
+            owner_names: RefCell::new(HashMap::new()),
+            type_ahead: String::new(),
+            type_ahead_at: 0.0,
+            scroll_to_selected: false,
+            pending_delete: None,
+        }
+    }
+
+    /// Current sorting, saved with the settings.
+    pub fn layout(&self) -> TreeLayout {
+        TreeLayout {
+            sort_column: self.sort_column,
+            ascending: self.ascending,
         }
     }
 
-    pub fn ui_zoomed(&mut self, ui: &mut Ui, node: &mut FileNode, zoom: f32) -> Option<TreeAction> {
-        let total_size = node.size;
-        
+    pub fn set_layout(&mut self, layout: &TreeLayout) {
+        self.sort_column = layout.sort_column;
+        self.ascending = layout.ascending;
+        self.rows_stale = true;
+    }
+
+    /// Switches what the bars show. Sorting by size follows along to
+    /// sorting by items and back.
+    pub fn set_measure(&mut self, measure: Measure) {
+        if self.measure == measure {
+            return;
+        }
+        self.sort_column = match (self.sort_column, measure) {
+            (TreeColumn::Size, Measure::Items) => TreeColumn::Items,
+            (TreeColumn::Items, Measure::Bytes) => TreeColumn::Size,
+            (column, _) => column,
+        };
+        self.measure = measure;
+        self.rows_stale = true;
+    }
+
+    /// Re-evaluates the search query and type filter into a match set,
+    /// but only when one of them or the tree has changed.
+    pub fn update_search(&mut self, root: &FileNode) {
+        let key = (self.search_query.clone(), self.type_filter.clone(), root.path.clone(), root.size);
+        if self.search_key.as_ref() == Some(&key) {
+            return;
+        }
+        self.search_key = Some(key);
+
+        match Query::parse(&self.search_query) {
+            Ok(query) => {
+                let query = query.with_type_filter(self.type_filter.as_ref());
+                self.search_error = None;
+                self.search_result = if query.is_empty() {
+                    None
+                } else {
+                    Some(SearchResult::evaluate(root, &query))
+                };
+                self.rows_stale = true;
+            }
+            // Keep showing the last valid result while the query is being typed
+            Err(e) => self.search_error = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn match_count(&self) -> Option<usize> {
+        self.search_result.as_ref().map(|r| r.matches.len())
+    }
+
+    pub fn is_flattened(&self) -> bool {
+        self.flatten_results && self.search_result.is_some()
+    }
+
+    /// Every match listed with its full path instead of nested in the tree.
+    pub fn flat_results_ui(&mut self, ui: &mut Ui, root: &FileNode) -> Option<TreeAction> {
+        let Some(result) = &self.search_result else {
+            return None;
+        };
+        let mut action = None;
+        let mut clicked = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 6.0;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::remainder().at_least(200.0).clip(true))
+            .column(Column::auto().at_least(90.0))
+            .header(row_height, |mut header| {
+                header.col(|ui| { ui.strong("Path"); });
+                header.col(|ui| { ui.strong("Size"); });
+            })
+            .body(|body| {
+                body.rows(row_height, result.matches.len(), |mut row| {
+                    let path = &result.matches[row.index()];
+                    let node = root.find(path);
+                    row.col(|ui| {
+                        let icon = if node.is_some_and(|n| n.is_dir) { "📁 " } else { "" };
+                        let is_selected = self.selected_path.as_ref() == Some(path);
+                        let response = ui.selectable_label(is_selected, format!("{}{}", icon, path.to_string_lossy()));
+                        item_context_menu(&response, path, &mut action);
+                        if response.clicked() {
+                            clicked = Some(path.clone());
+                        }
+                    });
+                    row.col(|ui| {
+                        let size = node.map_or(0, |n| n.size);
+                        ui.label(egui::RichText::new(format_size(size, DECIMAL)).monospace().weak());
+                    });
+                });
+            });
+
+        if clicked.is_some() {
+            self.selected_path = clicked;
+        }
+        action
+    }
+
+    /// The focused folder, or the whole tree if nothing (or something no
+    /// longer present) is focused.
+    fn shown_root<'a>(&self, root: &'a FileNode) -> &'a FileNode {
+        self.focus.as_deref().and_then(|f| root.find(f)).unwrap_or(root)
+    }
+
+    /// Keyboard control in the style of ncdu, plus the bar showing the
+    /// focused folder. Call before drawing the tree.
+    pub fn navigation_ui(&mut self, ui: &mut Ui, root: &FileNode) -> Option<TreeAction> {
+        if self.focus.as_deref().is_some_and(|f| root.find(f).is_none()) {
+            self.focus = None;
+        }
+        let action = self.delete_confirmation(ui.ctx());
+
+        let mut go_up = false;
+        ui.horizontal(|ui| {
+            if let Some(focus) = &self.focus {
+                go_up = ui.button("⏴ Up").on_hover_text("Backspace").clicked();
+                ui.label(egui::RichText::new(format!("📁 {}", focus.display())).strong());
+            }
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.label(egui::RichText::new("⌨ Keys").small().weak()).on_hover_text(KEYBOARD_HELP);
+            });
+        });
+        if go_up {
+            self.go_up(root);
+        }
+
+        if self.pending_delete.is_none() && !ui.ctx().wants_keyboard_input() {
+            self.handle_keys(ui, root);
+        }
+        action
+    }
+
+    fn handle_keys(&mut self, ui: &mut Ui, root: &FileNode) {
+        let keys = [Key::ArrowUp, Key::ArrowDown, Key::ArrowLeft, Key::ArrowRight, Key::Home, Key::End, Key::Enter, Key::Backspace, Key::Delete];
+        let (pressed, typed, now) = ui.input_mut(|i| {
+            let pressed: Vec<Key> = keys.into_iter().filter(|k| i.consume_key(Modifiers::NONE, *k)).collect();
+            let typed: String = i.events.iter()
+                .filter_map(|e| match e {
+                    egui::Event::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect();
+            (pressed, typed, i.time)
+        });
+        let ctx = ui.ctx().clone();
+
+        for key in pressed {
+            self.refresh_rows(&ctx, root);
+            let shown = self.shown_root(root);
+            let rows = &self.rows;
+            let Some(last) = rows.len().checked_sub(1) else {
+                return;
+            };
+            let current = self.selected_path.as_ref().and_then(|p| rows.iter().position(|r| r.path == *p));
+            let selected = current.and_then(|i| node_at(shown, &rows[i].indices));
+            let is_open_dir = current.zip(selected)
+                .is_some_and(|(i, n)| is_expandable(n) && is_open(&ctx, &n.path, rows[i].depth == 0));
+            let mut fold = None;
+            let mut go_up = false;
+            let mut entered = false;
+            let target = match key {
+                Key::ArrowDown => Some(current.map_or(0, |i| (i + 1).min(last))),
+                Key::ArrowUp => Some(current.map_or(0, |i| i.saturating_sub(1))),
+                Key::Home => Some(0),
+                Key::End => Some(last),
+                Key::ArrowRight => match (current, selected) {
+                    (Some(i), Some(_)) if is_open_dir => rows.get(i + 1).filter(|r| r.depth > rows[i].depth).map(|_| i + 1),
+                    (_, Some(node)) if is_expandable(node) => {
+                        fold = Some((node.path.clone(), true));
+                        None
+                    }
+                    _ => None,
+                },
+                Key::ArrowLeft => match (current, selected) {
+                    (_, Some(node)) if is_open_dir => {
+                        fold = Some((node.path.clone(), false));
+                        None
+                    }
+                    (Some(i), _) => rows[..i].iter().rposition(|r| r.depth < rows[i].depth),
+                    _ => None,
+                },
+                Key::Enter => {
+                    if let Some(node) = selected.filter(|n| is_expandable(n)) {
+                        fold = Some((node.path.clone(), true));
+                        self.focus = Some(node.path.clone());
+                        self.selected_path = Some(node.path.clone());
+                        entered = true;
+                    }
+                    None
+                }
+                Key::Backspace => {
+                    go_up = true;
+                    None
+                }
+                Key::Delete => {
+                    // The first row is the shown root itself, which is never deleted.
+                    if let Some(i) = current.filter(|&i| rows[i].depth > 0) {
+                        // Keep the cursor in place, like ncdu: on the next row
+                        // outside the deleted item, else the previous one.
+                        let next = rows[i + 1..].iter().find(|r| !r.path.starts_with(&rows[i].path))
+                            .or(i.checked_sub(1).map(|p| &rows[p]))
+                            .map(|r| r.path.clone());
+                        self.pending_delete = Some((rows[i].path.clone(), next));
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let Some(index) = target {
+                self.selected_path = Some(rows[index].path.clone());
+                self.scroll_to_selected = true;
+            }
+            if let Some((path, open)) = fold {
+                self.set_open(&ctx, &path, open);
+            }
+            if go_up {
+                self.go_up(root);
+            }
+            if entered {
+                // Start on the first child in the current order.
+                self.refresh_rows(&ctx, root);
+                if let Some(first) = self.rows.get(1) {
+                    self.selected_path = Some(first.path.clone());
+                }
+                self.scroll_to_selected = true;
+            }
+        }
+
+        if typed == "/" {
+            ctx.memory_mut(|m| m.request_focus(search_field_id()));
+        } else if !typed.is_empty() {
+            self.type_ahead(&ctx, root, &typed, now);
+        }
+    }
+
+    /// Jumps to the next visible row whose name starts with what was typed
+    /// within the last second.
+    fn type_ahead(&mut self, ctx: &egui::Context, root: &FileNode, typed: &str, now: f64) {
+        if now - self.type_ahead_at > TYPE_AHEAD_TIMEOUT {
+            self.type_ahead.clear();
+        }
+        self.type_ahead_at = now;
+        self.type_ahead.push_str(&typed.to_lowercase());
+
+        self.refresh_rows(ctx, root);
+        let rows = &self.rows;
+        let current = self.selected_path.as_ref().and_then(|p| rows.iter().position(|r| r.path == *p));
+        // A longer prefix may still match the current row; a first letter moves on.
+        let start = match current {
+            Some(i) if self.type_ahead.chars().count() > 1 => i,
+            Some(i) => i + 1,
+            None => 0,
+        };
+        let found = (0..rows.len()).map(|offset| &rows[(start + offset) % rows.len()]).find(|row| {
+            row.path.file_name().is_some_and(|n| n.to_string_lossy().to_lowercase().starts_with(&self.type_ahead))
+        });
+        if let Some(row) = found {
+            self.selected_path = Some(row.path.clone());
+            self.scroll_to_selected = true;
+        }
+    }
+
+    /// Leaves the focused folder for its parent, keeping it selected.
+    fn go_up(&mut self, root: &FileNode) {
+        let Some(focus) = self.focus.take() else {
+            return;
+        };
+        self.focus = focus.parent()
+            .filter(|parent| *parent != root.path && root.find(parent).is_some())
+            .map(Path::to_path_buf);
+        self.selected_path = Some(focus);
+        self.scroll_to_selected = true;
+    }
+
+    fn set_open(&mut self, ctx: &egui::Context, path: &Path, open: bool) {
+        let mut state = CollapsingState::load_with_default_open(ctx, node_id(path), false);
+        state.set_open(open);
+        state.store(ctx);
+        self.rows_stale = true;
+    }
+
+    /// Rows in drawing order: open folders show their children sorted by
+    /// the current column, and an active search hides what it does not
+    /// match. Only rebuilt when something they depend on changed.
+    fn refresh_rows(&mut self, ctx: &egui::Context, root: &FileNode) {
+        let key = (root.path.clone(), root.size, root.items(), self.focus.clone());
+        if !self.rows_stale && self.rows_key.as_ref() == Some(&key) {
+            return;
+        }
+        let shown = self.shown_root(root);
+        let mut rows = Vec::new();
+        self.collect_rows(ctx, shown, Vec::new(), self.measure.of(shown), &mut rows);
+        self.rows = rows;
+        self.rows_key = Some(key);
+        self.rows_stale = false;
+    }
+
+    fn collect_rows(&self, ctx: &egui::Context, node: &FileNode, indices: Vec<usize>, parent_total: u64, rows: &mut Vec<Row>) {
+        if self.search_result.as_ref().is_some_and(|r| !r.visible.contains(&node.path)) {
+            return;
+        }
+        let depth = indices.len();
+        let open = is_expandable(node) && is_open(ctx, &node.path, depth == 0);
+        rows.push(Row { path: node.path.clone(), indices: indices.clone(), depth, parent_total });
+        if !open {
+            return;
+        }
+        let mut order: Vec<usize> = (0..node.children.len()).collect();
+        order.sort_by(|&a, &b| self.compare(&node.children[a], &node.children[b]));
+        for index in order {
+            let mut child_indices = indices.clone();
+            child_indices.push(index);
+            self.collect_rows(ctx, &node.children[index], child_indices, self.measure.of_children(node), rows);
+        }
+    }
+
+    /// Orders siblings by the sort column, then by name.
+    fn compare(&self, a: &FileNode, b: &FileNode) -> Ordering {
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+        let ordering = match self.sort_column {
+            TreeColumn::Name => by_name(),
+            TreeColumn::Size => a.size.cmp(&b.size),
+            TreeColumn::Percent => self.measure.of(a).cmp(&self.measure.of(b)),
+            TreeColumn::Allocated => a.disk_usage.cmp(&b.disk_usage),
+            TreeColumn::Items => a.items().cmp(&b.items()),
+            TreeColumn::Modified => last_modified(a).cmp(&last_modified(b)),
+            TreeColumn::Owner => self.owner_label(a).cmp(&self.owner_label(b)),
+        };
+        let ordering = if self.ascending { ordering } else { ordering.reverse() };
+        ordering.then_with(by_name)
+    }
+
+    fn owner_label(&self, node: &FileNode) -> String {
+        match node.owner {
+            Some(uid) if self.resolve_owners => self.owner_names.borrow_mut()
+                .entry(uid)
+                .or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string()))
+                .clone(),
+            Some(uid) => uid.to_string(),
+            None => String::new(),
+        }
+    }
+
+    fn delete_confirmation(&mut self, ctx: &egui::Context) -> Option<TreeAction> {
+        let (path, next) = self.pending_delete.clone()?;
+        let mut action = None;
+        let mut close = false;
+        egui::Window::new("Move to Trash?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Move {} to the trash?", path.display()));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let confirm = ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter));
+                    if ui.button("Move to Trash").clicked() || confirm {
+                        action = Some(TreeAction::Delete(path.clone()));
+                        self.selected_path = next.clone();
+                        close = true;
+                    }
+                    let cancel = ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape));
+                    if ui.button("Cancel").clicked() || cancel {
+                        close = true;
+                    }
+                });
+            });
+        if close {
+            self.pending_delete = None;
+        }
+        action
+    }
+
+    pub fn ui_zoomed(&mut self, ui: &mut Ui, root: &FileNode, zoom: f32) -> Option<TreeAction> {
         // Apply zoom to the UI style for this scope
         let mut style = ui.style_mut().clone();
         style.text_styles.iter_mut().for_each(|(_, font_id)| {
@@ -33,85 +519,143 @@ impl TreeView {
         style.spacing.item_spacing *= zoom;
         style.spacing.indent *= zoom;
         style.spacing.interact_size *= zoom;
-        
+
         ui.scope(|ui| {
             ui.set_style(style);
-            self.recursive_tree(ui, node, total_size, zoom)
+            self.table_ui(ui, root, zoom)
         }).inner
     }
 
-    fn recursive_tree(&mut self, ui: &mut Ui, node: &mut FileNode, parent_size: u64, zoom: f32) -> Option<TreeAction> {
-        // Filter by search query
-        if !self.search_query.is_empty() && !node.name.to_lowercase().contains(&self.search_query.to_lowercase()) {
-            if node.is_dir {
-                let mut has_matching_child = false;
-                for child in &node.children {
-                    if self.matches_search(child) {
-                        has_matching_child = true;
-                        break;
-                    }
-                }
-                if !has_matching_child {
-                    return None;
-                }
-            } else {
-                return None;
+    /// One table row per visible item, the name indented by depth, under a
+    /// header that sorts by the clicked column.
+    fn table_ui(&mut self, ui: &mut Ui, root: &FileNode, zoom: f32) -> Option<TreeAction> {
+        self.refresh_rows(ui.ctx(), root);
+        let shown = self.shown_root(root);
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 6.0 * zoom;
+        let indent = ui.spacing().indent;
+
+        let mut action = None;
+        let mut clicked = None;
+        let mut toggled = None;
+        let mut sort_by = None;
+
+        let mut table = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .auto_shrink([false; 2])
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::remainder().at_least(220.0 * zoom).clip(true))
+            .column(Column::auto().at_least(80.0 * zoom))
+            .column(Column::auto().at_least(80.0 * zoom))
+            .column(Column::auto().at_least(60.0 * zoom))
+            .column(Column::auto().at_least(60.0 * zoom))
+            .column(Column::auto().at_least(120.0 * zoom))
+            .column(Column::auto().at_least(60.0 * zoom).clip(true));
+        if std::mem::take(&mut self.scroll_to_selected) {
+            if let Some(index) = self.selected_path.as_ref().and_then(|p| self.rows.iter().position(|r| r.path == *p)) {
+                table = table.scroll_to_row(index, None);
             }
         }
 
-        let size_text = format_size(node.size, DECIMAL);
-        let percentage = if parent_size > 0 {
-            (node.size as f32 / parent_size as f32) * 100.0
-        } else {
-            0.0
-        };
-
-        let mut action = None;
-        let is_selected = self.selected_path.as_ref() == Some(&node.path);
-        
-        if node.is_dir {
-            let id = ui.make_persistent_id(&node.path);
-            let header = egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false);
-            
-            header.show_header(ui, |ui| {
-                ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-                    self.draw_percentage_bar(ui, percentage, zoom);
-                    ui.add_space(5.0 * zoom);
-                    
-                    let response = ui.selectable_label(is_selected, &node.name);
-                    self.handle_response(ui, &response, node, &mut action);
-                    
-                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        ui.add_space(10.0 * zoom);
-                        ui.label(egui::RichText::new(size_text).monospace().weak());
+        table
+            .header(row_height + 4.0, |mut header| {
+                for (label, column) in COLUMNS {
+                    header.col(|ui| {
+                        if self.header_clicked(ui, label, column) {
+                            sort_by = Some(column);
+                        }
                     });
-                });
-            }).body(|ui| {
-                for child in &mut node.children {
-                    if let Some(act) = self.recursive_tree(ui, child, node.size, zoom) {
-                        action = Some(act);
-                    }
                 }
-            });
-        } else {
-            ui.horizontal(|ui| {
-                ui.add_space(20.0 * zoom); // Indent files to align with folder icons
-                self.draw_percentage_bar(ui, percentage, zoom);
-                ui.add_space(5.0 * zoom);
-                
-                let response = ui.selectable_label(is_selected, &node.name);
-                self.handle_response(ui, &response, node, &mut action);
-                
-                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                    ui.add_space(10.0 * zoom);
-                    ui.label(egui::RichText::new(size_text).monospace().weak());
+            })
+            .body(|body| {
+                body.rows(row_height, self.rows.len(), |mut row| {
+                    let item = &self.rows[row.index()];
+                    let Some(node) = node_at(shown, &item.indices) else {
+                        return;
+                    };
+                    let is_selected = self.selected_path.as_ref() == Some(&node.path);
+                    row.col(|ui| {
+                        ui.add_space(item.depth as f32 * indent);
+                        if is_expandable(node) {
+                            let open = is_open(ui.ctx(), &node.path, item.depth == 0);
+                            let arrow = egui::Button::new(if open { "⏷" } else { "⏵" }).frame(false);
+                            if ui.add(arrow).clicked() {
+                                toggled = Some((node.path.clone(), !open));
+                            }
+                        } else {
+                            ui.add_space(ui.spacing().interact_size.y);
+                        }
+                        let response = ui.selectable_label(is_selected, &node.name).on_hover_text(tooltip(node));
+                        item_context_menu(&response, &node.path, &mut action);
+                        if response.clicked() {
+                            clicked = Some(node.path.clone());
+                        }
+                        if response.double_clicked() && is_expandable(node) {
+                            toggled = Some((node.path.clone(), !is_open(ui.ctx(), &node.path, item.depth == 0)));
+                        }
+                    });
+                    row.col(|ui| {
+                        let size = if node.excluded { "excluded".to_string() } else { format_size(node.size, DECIMAL) };
+                        ui.label(RichText::new(size).monospace());
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(format_size(node.disk_usage, DECIMAL)).monospace().weak());
+                    });
+                    row.col(|ui| {
+                        if is_expandable(node) {
+                            ui.label(RichText::new(node.items().to_string()).monospace().weak());
+                        }
+                    });
+                    row.col(|ui| {
+                        let percentage = if item.parent_total > 0 {
+                            (self.measure.of(node) as f32 / item.parent_total as f32) * 100.0
+                        } else {
+                            0.0
+                        };
+                        self.draw_percentage_bar(ui, percentage, zoom);
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(format_time(last_modified(node))).monospace().weak());
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(self.owner_label(node)).weak());
+                    });
                 });
             });
+
+        if clicked.is_some() {
+            self.selected_path = clicked;
+        }
+        if let Some((path, open)) = toggled {
+            self.set_open(ui.ctx(), &path, open);
+        }
+        if let Some(column) = sort_by {
+            self.toggle_sort(column);
         }
-        
         action
     }
 
+    fn header_clicked(&self, ui: &mut Ui, label: &str, column: TreeColumn) -> bool {
+        let arrow = match (self.sort_column == column, self.ascending) {
+            (true, true) => " ⏶",
+            (true, false) => " ⏷",
+            _ => "",
+        };
+        ui.button(RichText::new(format!("{}{}", label, arrow)).strong()).clicked()
+    }
+
+    /// Clicking the sorted column flips the direction; another column
+    /// starts A to Z for text and largest or newest first otherwise.
+    fn toggle_sort(&mut self, column: TreeColumn) {
+        if self.sort_column == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.sort_column = column;
+            self.ascending = matches!(column, TreeColumn::Name | TreeColumn::Owner);
+        }
+        self.rows_stale = true;
+    }
+
     fn draw_percentage_bar(&self, ui: &mut Ui, percentage: f32, zoom: f32) {
         let (rect, _) = ui.allocate_at_least(Vec2::new(45.0 * zoom, 16.0 * zoom), egui::Sense::hover());
         let painter = ui.painter();
@@ -131,34 +675,6 @@ impl TreeView {
         );
     }
 
-    fn matches_search(&self, node: &FileNode) -> bool {
-        if node.name.to_lowercase().contains(&self.search_query.to_lowercase()) {
-            return true;
-        }
-        for child in &node.children {
-            if self.matches_search(child) {
-                return true;
-            }
-        }
-        false
-    }
-
-    fn handle_response(&mut self, _ui: &mut Ui, response: &egui::Response, node: &FileNode, action: &mut Option<TreeAction>) {
-        response.context_menu(|ui| {
-            if ui.button("Open").clicked() {
-                *action = Some(TreeAction::Open(node.path.clone()));
-                ui.close_menu();
-            }
-            if ui.button("Delete").clicked() {
-                *action = Some(TreeAction::Delete(node.path.clone()));
-                ui.close_menu();
-            }
-        });
-        if response.clicked() {
-            self.selected_path = Some(node.path.clone());
-        }
-    }
-
     fn get_percentage_color(&self, p: f32) -> Color32 {
         if p > 50.0 {
             Color32::from_rgb(255, 100, 100)
@@ -169,3 +685,51 @@ impl TreeView {
         }
     }
 }
+
+fn is_expandable(node: &FileNode) -> bool {
+    node.is_dir && !node.excluded
+}
+
+fn node_id(path: &Path) -> egui::Id {
+    egui::Id::new(("tree_node", path))
+}
+
+/// Whether a folder is expanded. The top row starts out expanded.
+fn is_open(ctx: &egui::Context, path: &Path, default_open: bool) -> bool {
+    CollapsingState::load_with_default_open(ctx, node_id(path), default_open).is_open()
+}
+
+fn node_at<'a>(root: &'a FileNode, indices: &[usize]) -> Option<&'a FileNode> {
+    indices.iter().try_fold(root, |node, &i| node.children.get(i))
+}
+
+/// When a file was modified, or the newest file in a folder.
+fn last_modified(node: &FileNode) -> Option<SystemTime> {
+    if node.is_dir { node.newest } else { node.modified }
+}
+
+fn tooltip(node: &FileNode) -> String {
+    let times = if node.is_dir {
+        format!("Newest file: {}\nOldest file: {}", format_time(node.newest), format_time(node.oldest))
+    } else {
+        format!(
+            "Modified: {}\nAccessed: {}\nChanged: {}",
+            format_time(node.modified),
+            format_time(node.accessed),
+            format_time(node.changed)
+        )
+    };
+    format!("{}{}", times, storage_note(node))
+}
+
+/// Disk usage, hard link and read error lines for an item's tooltip.
+fn storage_note(node: &FileNode) -> String {
+    let mut note = format!("\nOn disk: {}", format_size(node.disk_usage, DECIMAL));
+    if let Some(inode) = node.hard_link {
+        note.push_str(&format!("\nHard link (inode {}), other links count it too", inode));
+    }
+    if node.read_error {
+        note.push_str("\n⚠ Could not be read completely");
+    }
+    note
+}